@@ -23,10 +23,64 @@ impl App {
     pub async fn run(&'static self) -> std::result::Result<(), crate::errors::BackendError> {
         let cfg = Config::from_env();
         let http_cfg = HttpConfig::from_env();
+        let webhook_poll_interval = std::time::Duration::from_secs(cfg.webhook_poll_seconds);
+        let purge_poll_interval = std::time::Duration::from_secs(cfg.purge_poll_seconds);
+        let trending_refresh_interval = std::time::Duration::from_secs(cfg.trending_refresh_seconds);
+        let event_prune_interval = std::time::Duration::from_secs(cfg.event_buffer_minutes as u64 * 60);
 
         let mut state = AppState { server: backend::Server::with_config(cfg) };
         state.server.connect().await?;
 
+        // background delivery of queued webhook payloads
+        let webhook_server = state.server.clone();
+        async_std::task::spawn(async move {
+            loop {
+                async_std::task::sleep(webhook_poll_interval).await;
+                if let Err(err) = webhook_server.deliver_webhooks().await {
+                    eprintln!("webhook delivery failed: {}", err);
+                }
+            }
+        });
+
+        // permanent removal of deleted content once it cannot be restored
+        let purge_server = state.server.clone();
+        async_std::task::spawn(async move {
+            loop {
+                async_std::task::sleep(purge_poll_interval).await;
+                if let Err(err) = purge_server.purge_deleted().await {
+                    eprintln!("purging deleted content failed: {}", err);
+                }
+                if let Err(err) = purge_server.purge_orphan_uploads().await {
+                    eprintln!("purging orphan uploads failed: {}", err);
+                }
+            }
+        });
+
+        // events are dropped once they are too old to be replayed
+        let event_server = state.server.clone();
+        async_std::task::spawn(async move {
+            loop {
+                async_std::task::sleep(event_prune_interval).await;
+                if let Err(err) = event_server.prune_events().await {
+                    eprintln!("pruning events failed: {}", err);
+                }
+            }
+        });
+
+        // rankings are refreshed right away, so trending is available from start
+        let trending_server = state.server.clone();
+        async_std::task::spawn(async move {
+            loop {
+                if let Err(err) = trending_server.refresh_trending().await {
+                    eprintln!("refreshing trending articles failed: {}", err);
+                }
+                if let Err(err) = trending_server.prune_views().await {
+                    eprintln!("pruning article views failed: {}", err);
+                }
+                async_std::task::sleep(trending_refresh_interval).await;
+            }
+        });
+
         let mut app = tide::with_state(state);
 
         app.at("/api/users").post(register);
@@ -34,21 +88,68 @@ impl App {
         app.at("/api/users/login").post(login);
         app.at("/api/user").get(current_user);
         app.at("/api/user").put(update_user);
+        app.at("/api/user/bookmarks").get(bookmarks);
+        app.at("/api/user/tags").get(followed_tags);
+        app.at("/api/user/stats").get(user_stats);
         app.at("/api/profiles/:username").get(profile);
+        app.at("/api/profiles/:username/followers").get(followers);
+        app.at("/api/profiles/:username/following").get(following);
         app.at("/api/profiles/:username/follow").post(follow);
         app.at("/api/profiles/:username/follow").delete(unfollow);
+        app.at("/api/profiles/:username/block").post(block);
+        app.at("/api/profiles/:username/block").delete(unblock);
+        app.at("/api/profiles/:username/mute").post(mute);
+        app.at("/api/profiles/:username/mute").delete(unmute);
         app.at("/api/articles").post(create_article);
         app.at("/api/articles/feed").get(feed_articles);
         app.at("/api/articles").get(get_articles);
         app.at("/api/articles/:slug").put(update_article);
         app.at("/api/articles/:slug").get(get_article);
         app.at("/api/articles/:slug").delete(delete_article);
+        app.at("/api/articles/:slug/restore").post(restore_article);
+        app.at("/api/articles/:slug/related").get(related_articles);
         app.at("/api/articles/:slug/favorite").post(favorite_article);
         app.at("/api/articles/:slug/favorite").delete(unfavorite_article);
+        app.at("/api/articles/:slug/bookmark").post(bookmark_article);
+        app.at("/api/articles/:slug/bookmark").delete(unbookmark_article);
         app.at("/api/articles/:slug/comments").post(add_comment);
         app.at("/api/articles/:slug/comments").get(get_comments);
+        app.at("/api/articles/:slug/comments/:id").put(update_comment);
         app.at("/api/articles/:slug/comments/:id").delete(delete_comment);
+        app.at("/api/articles/:slug/comments/:id/restore").post(restore_comment);
+        app.at("/api/articles/:slug/reactions/:kind").post(react_to_article);
+        app.at("/api/articles/:slug/reactions/:kind").delete(unreact_to_article);
+        app.at("/api/articles/:slug/comments/:id/reactions/:kind").post(react_to_comment);
+        app.at("/api/articles/:slug/comments/:id/reactions/:kind").delete(unreact_to_comment);
+        app.at("/api/articles/:slug/report").post(report_article);
+        app.at("/api/articles/:slug/comments/:id/report").post(report_comment);
+        app.at("/api/reports").get(get_reports);
+        app.at("/api/reports/:id/dismiss").post(dismiss_report);
+        app.at("/api/reports/:id/moderate").post(moderate_report);
         app.at("/api/tags").get(get_tags);
+        app.at("/api/tags/cloud").get(tag_cloud);
+        app.at("/api/tags/:tag").get(get_tag);
+        app.at("/api/tags/:tag").put(update_tag);
+        app.at("/api/tags/:tag/aliases").post(add_tag_alias);
+        app.at("/api/tags/:tag/aliases/:alias").delete(remove_tag_alias);
+        app.at("/api/tags/:tag/merge").post(merge_tags);
+        app.at("/api/tags/:tag/follow").post(follow_tag);
+        app.at("/api/tags/:tag/follow").delete(unfollow_tag);
+        app.at("/api/notifications").get(get_notifications);
+        app.at("/api/notifications/unread").get(unread_notifications_count);
+        app.at("/api/notifications/read").post(mark_all_notifications_read);
+        app.at("/api/notifications/preferences").get(notification_preferences);
+        app.at("/api/notifications/preferences").put(update_notification_preferences);
+        app.at("/api/notifications/:id/read").post(mark_notification_read);
+        app.at("/api/events").get(events);
+        app.at("/api/webhooks").post(register_webhook);
+        app.at("/api/webhooks").get(get_webhooks);
+        app.at("/api/webhooks/:id").delete(delete_webhook);
+        app.at("/api/webhooks/:id/deliveries").get(webhook_deliveries);
+        app.at("/api/uploads").post(upload);
+        app.at("/uploads/:id").get(serve_upload);
+        app.at("/api/user/avatar").post(set_avatar);
+        app.at("/api/profiles/:username/avatar").get(avatar);
      
         let hp = format!("{}:{}", http_cfg.host, http_cfg.http_port); 
         app.listen(hp).await?;
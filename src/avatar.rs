@@ -0,0 +1,102 @@
+use image::{imageops, codecs::png, io::Limits, DynamicImage, ImageEncoder, ImageFormat, Rgb, RgbImage};
+use sha2::Digest;
+use crate::errors::BackendError;
+
+// side lengths of stored avatars, the largest one is used as profile image
+pub(crate) const AVATAR_SIZES: [u32; 3] = [256, 128, 64];
+// bounds of requested avatar size
+pub(crate) const AVATAR_MIN_SIZE: u32 = 16;
+pub(crate) const AVATAR_MAX_SIZE: u32 = 512;
+// bounds of decoded uploads, a small compressed file may claim huge dimensions
+const MAX_IMAGE_SIDE: u32 = 4096;
+const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+const IDENTICON_GRID: u32 = 5;
+const IDENTICON_BACKGROUND: Rgb<u8> = Rgb([240, 240, 240]);
+
+// square avatars of all sizes cropped from the center of the image turned upright,
+// re-encoding from pixels drops EXIF and any other metadata;
+// decoding and resizing take a while, so it is not to be run on async executor
+pub(crate) fn process(data: &[u8]) -> Result<Vec<(u32, Vec<u8>)>, BackendError> {
+    let format = image::guess_format(data)
+        .ok()
+        .filter(|format| matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP))
+        .ok_or(BackendError::ValidationError("avatar must be PNG, JPEG or WebP image".to_string()))?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_SIDE);
+    limits.max_image_height = Some(MAX_IMAGE_SIDE);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+    let mut reader = image::io::Reader::with_format(std::io::Cursor::new(data), format);
+    reader.limits(limits);
+    let image = reader.decode()
+        .map_err(|err| BackendError::ValidationError(format!("avatar image cannot be decoded: {}", err)))?;
+    let image = upright(image, orientation(data));
+
+    let side = image.width().min(image.height());
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+
+    AVATAR_SIZES.iter()
+        .map(|size| encode_png(&square.resize_exact(*size, *size, imageops::FilterType::Lanczos3))
+            .map(|png| (*size, png)))
+        .collect()
+}
+
+// EXIF Orientation tag, 1 is upright and missing or unreadable metadata means it too
+fn orientation(data: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut std::io::Cursor::new(data))
+        .ok()
+        .and_then(|exif| exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0)))
+        .unwrap_or(1)
+}
+
+// cameras store pixels as sensor reads them and record how to turn them
+fn upright(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+// symmetric 5x5 pattern and its color taken from hash of the seed,
+// so the same user always gets the same picture
+pub(crate) fn identicon(seed: &str, size: u32) -> Result<Vec<u8>, BackendError> {
+    let hash = sha2::Sha256::digest(seed.as_bytes());
+    let color = Rgb([hash[0], hash[1], hash[2]]);
+
+    let icon = RgbImage::from_fn(size, size, |x, y| {
+        let column = x * IDENTICON_GRID / size;
+        let row = y * IDENTICON_GRID / size;
+        // right half mirrors the left one
+        let column = column.min(IDENTICON_GRID - 1 - column);
+        if hash[(3 + row * 3 + column) as usize] & 1 == 1 {
+            color
+        } else {
+            IDENTICON_BACKGROUND
+        }
+    });
+
+    encode_png(&DynamicImage::ImageRgb8(icon))
+}
+
+// avatars are small and served often, so best compression pays off
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, BackendError> {
+    // transparency is kept
+    let (pixels, color) = if image.color().has_alpha() {
+        (image.to_rgba8().into_raw(), image::ColorType::Rgba8)
+    } else {
+        (image.to_rgb8().into_raw(), image::ColorType::Rgb8)
+    };
+    let mut data = Vec::new();
+    png::PngEncoder::new_with_quality(&mut data, png::CompressionType::Best, png::FilterType::Adaptive)
+        .write_image(&pixels, image.width(), image.height(), color)
+        .map_err(|err| BackendError::UnexpectedError(format!("image encoding failed: {}", err)))?;
+    Ok(data)
+}
@@ -2,28 +2,53 @@
 // used for transforming http requests to model form, compile time guards
 // for requests that need be authenicated and for injecting "offline" tests 
 use sqlx::sqlite::{SqlitePool};
+use sha2::Digest;
 
 use crate::{config::Config, 
-    models::{user::*, article::*}, 
-    requests,
-    db, auth, filters, errors::BackendError
+    models::{user::*, article::*, reaction::ReactionKind, notification::*, event::*, webhook::*, report::*, tag::{self, Tag, TagCloud}, stats::AuthorStats, upload::{self, Upload, Avatar}}, 
+    requests, events, webhooks, storage,
+    db, auth, filters, markdown, avatar, errors::BackendError
 };
 
+use tide::prelude::*;
 use crate::requests::IntoAuthenticatedRequest;
+use validator::Validate;
+
+const WEBHOOK_DELIVERY_BATCH: i32 = 50;
+const TAG_SUGGESTIONS: i32 = 10;
+// age in hours at which an action counts half for trending
+const TRENDING_DECAY_HOURS: f64 = 12.0;
+const TAG_SUGGESTIONS_MAX: i32 = 50;
+const RELATED_ARTICLES: i32 = 5;
+const RELATED_ARTICLES_MAX: i32 = 20;
+// top-level comments per page, each one comes with its replies
+const COMMENT_THREADS: i32 = 20;
+const COMMENT_THREADS_MAX: i32 = 100;
 
 #[derive(Clone, Debug)]
 pub struct Server {
     config: Config,
     pub(crate) conn: Option<SqlitePool>,
+    bus: events::EventBus,
+    storage: std::sync::Arc<dyn storage::Storage>,
 }
 
 impl Server {
     pub fn with_config(config: Config) -> Self {
+        let storage = std::sync::Arc::new(storage::LocalStorage::new(&config.upload_dir));
         Self { 
             config,
             conn: None,
+            bus: events::EventBus::default(),
+            storage,
         }
     }
+
+    // replaces local file system storage of uploads
+    pub fn with_storage(mut self, storage: std::sync::Arc<dyn storage::Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
  
     pub async fn connect(&mut self) -> Result<(), sqlx::Error> {
         self.conn = Some(db::connect(&self.config).await?);
@@ -34,6 +59,187 @@ impl Server {
         self.config.secret.as_bytes()
     }
 
+    // username of optionally authenticated user, 
+    // expired or invalid token is treated as anonymous
+    fn viewer(&self, token: Option<&str>) -> Option<String> {
+        token
+            .and_then(|token| auth::Auth::authenticate(token, self.secret()).ok())
+            .map(|claims| claims.username)
+    }
+
+    // moderators are configured by username
+    fn moderator(&self, token: &str) -> Result<auth::Claims, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        if self.config.moderators.contains(&claims.username) {
+            Ok(claims)
+        } else {
+            Err(BackendError::Forbidden)
+        }
+    }
+
+    // content hidden by moderator remains visible to its author and moderators
+    fn can_see_hidden(&self, viewer: Option<&str>, author: &str) -> bool {
+        viewer.is_some_and(|viewer| 
+            viewer == author || self.config.moderators.iter().any(|moderator| moderator == viewer))
+    }
+
+    async fn visible_article(&self, slug: &str, viewer: Option<&str>) -> Result<ArticleResponse, BackendError> {
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+        let article_response = db::article::get_one(self.conn.as_ref().unwrap(), filter, viewer).await?;
+
+        if article_response.article.hidden && !self.can_see_hidden(viewer, &article_response.article.author) {
+            Err(BackendError::Forbidden)
+        } else {
+            Ok(article_response)
+        }
+    }
+
+    // suspended users cannot publish
+    async fn ensure_not_suspended(&self, username: &str) -> Result<(), BackendError> {
+        if db::user::is_suspended(self.conn.as_ref().unwrap(), username).await? {
+            Err(BackendError::Forbidden)
+        } else {
+            Ok(())
+        }
+    }
+
+    // persists event for replaying to reconnecting subscribers 
+    // and broadcasts it to the connected ones
+    async fn publish(&self, 
+        kind: EventKind, 
+        recipient: Option<&str>, 
+        article_slug: Option<&str>, 
+        payload: String
+    ) -> Result<(), BackendError> {
+        let event = db::event::store_event(self.conn.as_ref().unwrap(), kind, recipient, None, article_slug, payload).await?;
+
+        self.bus.publish(event);
+        Ok(())
+    }
+
+    // new article is stored once and announced to every follower of its author,
+    // subscribers pick it by the author they follow
+    async fn publish_to_followers(&self, article_response: &ArticleResponse) -> Result<(), BackendError> {
+        let event = db::event::store_event(self.conn.as_ref().unwrap(), 
+            EventKind::Article, 
+            None,
+            Some(&article_response.article.author),
+            Some(&article_response.article.slug), 
+            json!(article_response.clone().wrap()).to_string())
+            .await?;
+
+        self.bus.publish(event);
+        Ok(())
+    }
+
+    // removes events older than the replay buffer
+    pub async fn prune_events(&self) -> Result<(), BackendError> {
+        db::event::prune_events(self.conn.as_ref().unwrap(), self.config.event_buffer_minutes).await
+    }
+
+    // stores users mentioned in article body or in its comment and notifies 
+    // the newly mentioned ones who can see it, unless they have blocked the author,
+    // returns all users mentioned in the text
+    async fn record_mentions(&self, 
+        author: &str, 
+        article: &Article, 
+        comment_id: Option<i32>, 
+        body: &str,
+    ) -> Result<Vec<String>, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+
+        let added = db::mention::set_mentions(conn, article.id, comment_id, &markdown::mentions(body)).await?;
+        for username in added.iter() {
+            // not told about hidden content they cannot open
+            if article.hidden && !self.can_see_hidden(Some(username), &article.author) {
+                continue;
+            }
+            if db::user::is_blocked(conn, username, author).await? {
+                continue;
+            }
+            if let Err(err) = self.notify(&NewNotification {
+                kind: NotificationKind::Mention,
+                recipient: username,
+                actor: author,
+                article_slug: Some(&article.slug),
+                comment_id,
+            }).await {
+                eprintln!("notifying {} of mention failed: {}", username, err);
+            }
+        }
+
+        match comment_id {
+            Some(comment_id) => Ok(db::mention::get_comment_mentions(conn, comment_id).await?),
+            None => Ok(db::mention::get_mentions(conn, article.id).await?),
+        }
+    }
+
+    // queues event for delivery to subscribed webhooks of the users it is about
+    async fn dispatch(&self, event: WebhookEvent, owners: &[&str], data: impl Serialize) -> Result<(), BackendError> {
+        let payload = json!({
+            "event": event,
+            "createdAt": chrono::Utc::now().to_rfc3339(),
+            "data": data,
+        });
+        db::webhook::enqueue(self.conn.as_ref().unwrap(), event, owners, &payload.to_string()).await?;
+        Ok(())
+    }
+
+    async fn notify(&self, notification: &NewNotification<'_>) -> Result<(), BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+
+        if let Some(id) = db::notification::notify(conn, notification).await? {
+            let filter = filters::NotificationFilter::default()
+                .recipient(notification.recipient.to_string())
+                .id(id);
+            let notification_response = db::notification::get_notifications(conn, 
+                    &filter, 
+                    filters::LimitOffsetFilter::default().limit(1))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or(BackendError::NoNotificationFound(id))?;
+
+            self.publish(EventKind::Notification, 
+                Some(notification.recipient), 
+                notification.article_slug, 
+                json!(notification_response.wrap()).to_string())
+                .await?;
+        }
+        Ok(())
+    }
+
+    // stream of events for authenticated user, comment events on the 
+    // article being viewed are included, events persisted after
+    // last_event_id are replayed first
+    pub async fn subscribe(&self, 
+        token: &str, 
+        article_slug: Option<String>, 
+        last_event_id: Option<i64>
+    ) -> Result<events::Subscription, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        if let Some(slug) = article_slug.as_deref() {
+            self.visible_article(slug, Some(&claims.username)).await?;
+        }
+        let following = db::user::get_following(self.conn.as_ref().unwrap(), &claims.username).await?;
+        // subscribe before loading persisted events, so that nothing 
+        // published in between gets lost
+        let receiver = self.bus.subscribe();
+
+        let replay = match last_event_id {
+            Some(last_event_id) => db::event::events_since(self.conn.as_ref().unwrap(), 
+                    last_event_id, 
+                    &claims.username, 
+                    article_slug.as_deref())
+                .await?,
+            None => vec![],
+        };
+        let last_event_id = last_event_id.unwrap_or(0);
+
+        Ok(events::Subscription::new(claims.username, following, article_slug, replay, last_event_id, receiver))
+    }
+
     pub async fn register_user(&self, user_reg: requests::user::UserReg) 
         -> Result<LoggedInUser, BackendError> {
         
@@ -73,20 +279,72 @@ impl Server {
         Ok(LoggedInUser::from_user_and_token(user, token.to_string()))
     }
 
-    pub async fn profile(&self, username: &str) -> Result<Profile, BackendError> {
-        db::user::get_profile(self.conn.as_ref().unwrap(), username)
+    // profile is hidden from users it has blocked
+    pub async fn profile(&self, token: Option<&str>, username: &str) -> Result<Profile, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+
+        if let Some(viewer) = self.viewer(token) {
+            if db::user::is_blocked(conn, username, &viewer).await? {
+                return Err(BackendError::NoUserFound(username.to_string()));
+            }
+        }
+        db::user::get_profile(conn, username)
             .await
             .ok_or(BackendError::NoUserFound(username.to_string()))
     }
 
+    pub async fn followers(&self, 
+        token: Option<&str>,
+        username: &str,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleProfileResponse, BackendError> {
+        self.follow_list(token, username, db::user::FollowList::Followers, limit_offset).await
+    }
+
+    pub async fn following(&self, 
+        token: Option<&str>,
+        username: &str,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleProfileResponse, BackendError> {
+        self.follow_list(token, username, db::user::FollowList::Following, limit_offset).await
+    }
+
+    async fn follow_list(&self, 
+        token: Option<&str>,
+        username: &str,
+        list: db::user::FollowList,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleProfileResponse, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+        // empty list is distinguished from non-existing user
+        let profile = self.profile(token, username).await?;
+
+        let profiles = db::user::get_follow_list(conn, &profile.username, list, limit_offset).await?;
+        let profiles_count = db::user::count_follow_list(conn, &profile.username, list).await?;
+
+        Ok(MultipleProfileResponse { profiles, profiles_count })
+    }
+
     pub async fn update_user(&self, 
         token: &str, 
         update_user_req: requests::user::UserUpdateRequest) -> Result<LoggedInUser, BackendError> {
         let claims = auth::Auth::authenticate(token, self.secret())?;
-        let filter = filters::UpdateUserFilter::default().username(&claims.username);
+        let filter = filters::UpdateUserFilter::default()
+            .username(&claims.username)
+            .version(update_user_req.if_match);
         let update_user = UserUpdate::from(&update_user_req);
 
-        let user = db::user::update_user(self.conn.as_ref().unwrap(), &update_user, filter).await?;
+        let user = db::user::update_user(self.conn.as_ref().unwrap(), &update_user, filter)
+            .await
+            .map_err(|err| 
+                match err {
+                    // authenticated user exists, so nothing updated 
+                    // means the user has been modified meanwhile
+                    BackendError::NoUserFound(_) if update_user_req.if_match.is_some() =>
+                        BackendError::PreconditionFailed,
+                    err => err,
+                }
+            )?;
 
         Ok(LoggedInUser::from_user_and_token(user, token.to_string()))
     }
@@ -94,7 +352,34 @@ impl Server {
     pub async fn follow(&self, token: &str, celeb_name: &str) -> Result<Profile, BackendError> {
         let claims = auth::Auth::authenticate(token, self.secret())?;
 
-        db::user::follow(self.conn.as_ref().unwrap(), &claims.username, &celeb_name).await
+        if db::user::is_blocked(self.conn.as_ref().unwrap(), celeb_name, &claims.username).await? {
+            return Err(BackendError::Forbidden);
+        }
+
+        let (profile, followed) = db::user::follow(self.conn.as_ref().unwrap(), &claims.username, &celeb_name).await?;
+        // repeated follow changes nothing and is not announced again
+        if 0 == followed {
+            return Ok(profile);
+        }
+
+        // follow is stored already, failing to announce it does not undo that
+        if let Err(err) = self.notify(&NewNotification {
+            kind: NotificationKind::Follow,
+            recipient: &profile.username,
+            actor: &claims.username,
+            article_slug: None,
+            comment_id: None,
+        }).await {
+            eprintln!("notifying {} of follow failed: {}", profile.username, err);
+        }
+
+        if let Err(err) = self.dispatch(WebhookEvent::UserFollowed, &[&profile.username], json!({ 
+            "follower": claims.username,
+            "profile": &profile,
+        })).await {
+            eprintln!("dispatching follow of {} failed: {}", profile.username, err);
+        }
+        Ok(profile)
     }   
 
     pub async fn unfollow(&self, token: &str, celeb_name: &str) -> Result<Profile, BackendError> {
@@ -103,32 +388,198 @@ impl Server {
         db::user::unfollow(self.conn.as_ref().unwrap(), &claims.username, &celeb_name).await
     }
 
+    pub async fn block(&self, token: &str, username: &str) -> Result<Profile, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        if claims.username == username {
+            return Err(BackendError::ValidationError("users cannot block themselves".to_string()));
+        }
+
+        db::user::block(self.conn.as_ref().unwrap(), &claims.username, username).await
+    }
+
+    pub async fn unblock(&self, token: &str, username: &str) -> Result<Profile, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::user::unblock(self.conn.as_ref().unwrap(), &claims.username, username).await
+    }
+
+    pub async fn mute(&self, token: &str, username: &str) -> Result<Profile, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        if claims.username == username {
+            return Err(BackendError::ValidationError("users cannot mute themselves".to_string()));
+        }
+
+        db::user::mute(self.conn.as_ref().unwrap(), &claims.username, username).await
+    }
+
+    pub async fn unmute(&self, token: &str, username: &str) -> Result<Profile, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::user::unmute(self.conn.as_ref().unwrap(), &claims.username, username).await
+    }
+
     pub async fn create_article(&self, 
         token: &str, 
-        article_request: requests::article::CreateArticleRequest) -> Result<ArticleResponse, BackendError> {
+        mut article_request: requests::article::CreateArticleRequest) -> Result<ArticleResponse, BackendError> {
         
+        if let Some(tags) = article_request.tag_list.as_ref() {
+            article_request.tag_list = Some(db::tag::canonical_list(self.conn.as_ref().unwrap(), tags).await?);
+        }
         let create_req_auth = article_request.authenticate(token, self.secret())?; 
+        self.ensure_not_suspended(&create_req_auth.author).await?;
         let article = Article::from(create_req_auth);
 
-        db::article::create_article(self.conn.as_ref().unwrap(), article).await
+        let mut article_response = db::article::create_article(self.conn.as_ref().unwrap(), article).await?;
+
+        // article is created already, failing to announce it does not undo that
+        match self.record_mentions(&article_response.article.author, 
+            &article_response.article, None, &article_response.article.body).await {
+            Ok(mentions) => article_response.mentions = mentions,
+            Err(err) => eprintln!("recording mentions in article {} failed: {}", article_response.article.slug, err),
+        }
+        if let Err(err) = self.publish_to_followers(&article_response).await {
+            eprintln!("publishing article {} failed: {}", article_response.article.slug, err);
+        }
+        if let Err(err) = self.dispatch(WebhookEvent::ArticleCreated, 
+            &[&article_response.article.author], article_response.clone().wrap()).await {
+            eprintln!("dispatching article {} failed: {}", article_response.article.slug, err);
+        }
+        Ok(article_response)
     }
 
-    pub async fn get_article_by_slug(&self, slug: &str) -> Result<ArticleResponse, BackendError> {
-        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+    pub async fn get_article_by_slug(&self, 
+        token: Option<&str>, 
+        slug: &str
+    ) -> Result<ArticleResponse, BackendError> {
+        let viewer = self.viewer(token);
+
+        match self.visible_article(slug, viewer.as_deref()).await {
+            // slug might be outdated because the article title has been changed,
+            // in this case the article is served by its current slug
+            Err(BackendError::NoArticleFound) => {
+                let current_slug = db::article::slug_alias(self.conn.as_ref().unwrap(), slug)
+                    .await?
+                    .ok_or(BackendError::NoArticleFound)?;
 
-        db::article::get_one(self.conn.as_ref().unwrap(), filter).await
+                self.visible_article(&current_slug, viewer.as_deref()).await
+            },
+            res => res,
+        }
+    }
+
+    // article read by a client, view is counted unless it is by a crawler, 
+    // by the author or repeated within deduplication window
+    pub async fn view_article(&self, 
+        token: Option<&str>, 
+        slug: &str,
+        context: &requests::article::ViewContext,
+    ) -> Result<ArticleResponse, BackendError> {
+        let mut article_response = self.get_article_by_slug(token, slug).await?;
+        let viewer = self.viewer(token);
+        let own_article = viewer.as_deref() == Some(article_response.article.author.as_str());
+
+        let viewer_key = match (viewer, context.remote.as_ref()) {
+            (Some(username), _) => Some(format!("user:{}", username)),
+            (None, Some(remote)) => Some(format!("ip:{}", remote)),
+            (None, None) => None,
+        };
+
+        if let Some(viewer_key) = viewer_key.filter(|_| !context.is_crawler() && !own_article) {
+            let counted = db::article::record_view(self.conn.as_ref().unwrap(), 
+                article_response.article.id, &viewer_key, self.config.view_dedup_minutes).await?;
+            if counted {
+                article_response.article.views_count += 1;
+            }
+        }
+        Ok(article_response)
+    }
+
+    // adds bodyHtml when HTML format is requested
+    pub async fn render_article(&self, 
+        mut article_response: ArticleResponse,
+        format: filters::BodyFormat,
+    ) -> Result<ArticleResponse, BackendError> {
+        if format == filters::BodyFormat::Html {
+            article_response.body_html = Some(self.article_html(&article_response.article).await?);
+        }
+        Ok(article_response)
+    }
+
+    pub async fn render_articles(&self, 
+        mut articles: MultipleArticleResponse,
+        format: filters::BodyFormat,
+    ) -> Result<MultipleArticleResponse, BackendError> {
+        if format == filters::BodyFormat::Html {
+            for article_response in articles.articles.iter_mut() {
+                article_response.body_html = Some(self.article_html(&article_response.article).await?);
+            }
+        }
+        Ok(articles)
+    }
+
+    // comments are short and rendered on every request
+    pub fn render_comments(&self, 
+        mut comments: MultipleCommentResponse,
+        format: filters::BodyFormat,
+    ) -> MultipleCommentResponse {
+        if format == filters::BodyFormat::Html {
+            for comment_response in comments.comments.iter_mut() {
+                comment_response.body_html = Some(markdown::render_html(&comment_response.comment.body));
+            }
+        }
+        comments
+    }
+
+    // article body is rendered once per version
+    async fn article_html(&self, article: &Article) -> Result<String, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+
+        match db::article::cached_html(conn, article.id, article.version).await? {
+            Some(body_html) => Ok(body_html),
+            None => {
+                let body_html = markdown::render_html(&article.body);
+                db::article::cache_html(conn, article.id, article.version, &body_html).await?;
+                Ok(body_html)
+            },
+        }
     }
 
     pub async fn get_articles(&self, 
-        article_by: filters::ArticleFilterByValues,
+        token: Option<&str>,
+        mut article_by: filters::ArticleFilterByValues,
         order_by: filters::OrderByFilter<'_>,
         limit_offset: filters::LimitOffsetFilter
     ) -> Result<MultipleArticleResponse, BackendError> {
+        let viewer = self.viewer(token);
+        if let Some(tag) = article_by.tag.as_ref() {
+            article_by.tag = Some(self.canonical_tag(tag).await?);
+        }
 
         let articles = db::article::get_all(self.conn.as_ref().unwrap(), 
             article_by, 
             order_by, 
-            limit_offset)
+            limit_offset,
+            viewer.as_deref())
+        .await?;
+        Ok(MultipleArticleResponse::from_articles(articles))
+    }
+
+    pub async fn related_articles(&self, 
+        token: Option<&str>,
+        slug: &str,
+        limit_offset: filters::LimitOffsetFilter
+    ) -> Result<MultipleArticleResponse, BackendError> {
+        let viewer = self.viewer(token);
+        let article_response = self.visible_article(slug, viewer.as_deref()).await?;
+        let limit_offset = filters::LimitOffsetFilter {
+            limit: Some(limit_offset.limit.unwrap_or(RELATED_ARTICLES).clamp(1, RELATED_ARTICLES_MAX)),
+            offset: None,
+        };
+
+        let articles = db::article::get_related(self.conn.as_ref().unwrap(), 
+            article_response.article.id, 
+            limit_offset,
+            viewer.as_deref())
         .await?;
         Ok(MultipleArticleResponse::from_articles(articles))
     }
@@ -139,28 +590,47 @@ impl Server {
     ) -> Result<ArticleResponse, BackendError> {
     
     let update_req_auth = update_article_req.authenticate(token, self.secret())?;
+    self.ensure_not_suspended(&update_req_auth.author).await?;
     let update_by = filters::UpdateArticleFilter::from(&update_req_auth);
     let slug = update_by.slug;
+    let if_match = update_by.version;
 
     let res = match db::article::update_article(self.conn.as_ref().unwrap(), 
                                                 &update_req_auth.article_request.article, 
                                                 update_by).await {
-        Ok(article_response) => {
+        Ok(mut article_response) => {
+            // article is updated already, failing to announce it does not undo that
+            if update_req_auth.article_request.article.body.is_some() {
+                match self.record_mentions(&article_response.article.author, 
+                    &article_response.article, None, &article_response.article.body).await {
+                    Ok(mentions) => article_response.mentions = mentions,
+                    Err(err) => eprintln!("recording mentions in article {} failed: {}", article_response.article.slug, err),
+                }
+            }
+            if let Err(err) = self.dispatch(WebhookEvent::ArticleUpdated, 
+                &[&article_response.article.author], article_response.clone().wrap()).await {
+                eprintln!("dispatching article {} failed: {}", article_response.article.slug, err);
+            }
             Ok(article_response)
         },
         Err(err) => match err {
             // successful update returns the updated article, otherwise
             // NoArticleFound error is returned
             // if optimistic update fails, try to verify if this happened
-            // because user is not authorized to do so
+            // because user is not authorized to do so or the article
+            // has been modified since the version the update is based on
             BackendError::NoArticleFound => {
                 let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
-                if let Ok(article_response) = db::article::get_one(self.conn.as_ref().unwrap(), filter).await {
+                if let Ok(article_response) = db::article::get_one(self.conn.as_ref().unwrap(), filter, None).await {
                     auth::Auth::authorize(token, self.secret(), &article_response.article.author)?;
 
-                    Err(BackendError::UnexpectedError(
-                        " could not update article despite user was authorized to do so.".to_string())
-                    )
+                    if if_match.filter(|version| *version != article_response.article.version).is_some() {
+                        Err(BackendError::PreconditionFailed)
+                    } else {
+                        Err(BackendError::UnexpectedError(
+                            " could not update article despite user was authorized to do so.".to_string())
+                        )
+                    }
                 } else {
                     Err(BackendError::NoArticleFound)
                 }
@@ -187,7 +657,7 @@ impl Server {
         if 0 == query_res.rows_affected() {
                                             
             let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
-            if let Ok(article_response) = db::article::get_one(self.conn.as_ref().unwrap(), filter).await {
+            if let Ok(article_response) = db::article::get_one(self.conn.as_ref().unwrap(), filter, None).await {
 
                 auth::Auth::authorize(token, self.secret(), &article_response.article.author)?;
 
@@ -198,14 +668,47 @@ impl Server {
                 Err(BackendError::NoArticleFound)
             }
         } else {
+            if let Err(err) = self.dispatch(WebhookEvent::ArticleDeleted, &[&delete_req_auth.author], json!({ 
+                "article": { "slug": slug } 
+            })).await {
+                eprintln!("dispatching deletion of article {} failed: {}", slug, err);
+            }
             Ok(())
         }
     }
 
+    pub async fn restore_article(&self, token: &str, slug: &str) -> Result<ArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let conn = self.conn.as_ref().unwrap();
+
+        let query_res = db::article::restore_article(conn, 
+            slug, &claims.username, self.config.restore_window_minutes).await?;
+        if 0 == query_res.rows_affected() {
+            return Err(BackendError::NoArticleFound);
+        }
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+        db::article::get_one(conn, filter, Some(&claims.username)).await
+    }
+
     pub async fn favorite_article(&self, token: &str, slug: &str) -> Result<ArticleResponse, BackendError> {
         let claims = auth::Auth::authenticate(token, self.secret())?;
+        self.visible_article(slug, Some(&claims.username)).await?;
 
-        db::article::favorite_article(self.conn.as_ref().unwrap(), slug, &claims.username).await
+        let (article_response, favorited) = db::article::favorite_article(self.conn.as_ref().unwrap(), slug, &claims.username).await?;
+        if 0 == favorited {
+            return Ok(article_response);
+        }
+
+        if let Err(err) = self.notify(&NewNotification {
+            kind: NotificationKind::Favorite,
+            recipient: &article_response.article.author,
+            actor: &claims.username,
+            article_slug: Some(&article_response.article.slug),
+            comment_id: None,
+        }).await {
+            eprintln!("notifying {} of favorite failed: {}", article_response.article.author, err);
+        }
+        Ok(article_response)
     }
 
     pub async fn unfavorite_article(&self, token: &str, slug: &str) -> Result<ArticleResponse, BackendError> {
@@ -214,19 +717,67 @@ impl Server {
         db::article::unfavorite_article(self.conn.as_ref().unwrap(), slug, &claims.username).await
     }
 
+    // activity on articles of the authenticated author
+    pub async fn author_stats(&self, 
+        token: &str, 
+        filter: filters::StatsFilter,
+    ) -> Result<AuthorStats, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::stats::get_author_stats(self.conn.as_ref().unwrap(), &claims.username, filter).await
+    }
+
+    pub async fn bookmark_article(&self, token: &str, slug: &str) -> Result<ArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        self.visible_article(slug, Some(&claims.username)).await?;
+
+        db::article::bookmark_article(self.conn.as_ref().unwrap(), slug, &claims.username).await
+    }
+
+    pub async fn unbookmark_article(&self, token: &str, slug: &str) -> Result<ArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::article::unbookmark_article(self.conn.as_ref().unwrap(), slug, &claims.username).await
+    }
+
+    // reading list of the user, most recently bookmarked first
+    pub async fn bookmarks(&self, 
+        token: &str,
+        limit_offset: filters::LimitOffsetFilter
+    ) -> Result<MultipleArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        let filter = filters::ArticleFilterBookmarks { username: &claims.username };
+        let bookmarked_at = format!(
+            "(SELECT id FROM bookmarks WHERE article_id=articles.id AND username='{}')", 
+            claims.username);
+        let order_by = filters::OrderByFilter::Descending(&bookmarked_at);
+        
+        let articles = db::article::get_all(self.conn.as_ref().unwrap(), 
+            filter, 
+            order_by, 
+            limit_offset,
+            Some(&claims.username))
+        .await?;
+
+        Ok(MultipleArticleResponse::from_articles(articles))
+    }
+
     pub async fn feed_articles(&self, 
         token: &str,
+        feed_filter: filters::FeedFilter,
         limit_offset: filters::LimitOffsetFilter
     ) -> Result<MultipleArticleResponse, BackendError> {
         let claims = auth::Auth::authenticate(token, self.secret())?;
 
-        let filter = filters::ArticleFilterFeed { follower: &claims.username };
+        let filter = filters::ArticleFilterFeed { follower: &claims.username, mode: feed_filter.mode };
         let order_by = filters::OrderByFilter::Descending("updatedAt");
         
         let articles = db::article::get_all(self.conn.as_ref().unwrap(), 
             filter, 
             order_by, 
-            limit_offset)
+            limit_offset,
+            Some(&claims.username))
         .await?;
 
         Ok(MultipleArticleResponse::from_articles(articles))
@@ -238,14 +789,89 @@ impl Server {
     ) -> Result<CommentResponse, BackendError> {
 
         let add_req_auth = &add_comment_req.authenticate(token, self.secret())?;
+        self.ensure_not_suspended(&add_req_auth.author).await?;
 
         let comment_filter = filters::CommentFilterByValues::default()
             .article_slug(add_req_auth.article_request.article_slug);
         let comment_author = &add_req_auth.author;
         let comment_body = &add_req_auth.article_request.body;
+        let parent_id = add_req_auth.article_request.parent_id;
+
+        let article_response = self.visible_article(add_req_auth.article_request.article_slug, 
+            Some(comment_author)).await?;
+        // users blocked by article author cannot comment on it
+        if db::user::is_blocked(self.conn.as_ref().unwrap(), &article_response.article.author, comment_author).await? {
+            return Err(BackendError::Forbidden);
+        }
+
+        let depth = match parent_id {
+            Some(parent_id) => {
+                // replied comment must belong to the same article
+                let filter = filters::CommentFilterByValues::default()
+                    .id(parent_id)
+                    .article_slug(add_req_auth.article_request.article_slug);
+
+                let parent = db::article::get_comments(self.conn.as_ref().unwrap(), 
+                                                        filter, 
+                                                        filters::OrderByFilter::default(), 
+                                                        filters::LimitOffsetFilter::default().limit(1),
+                                                        None)
+                    .await?
+                    .into_iter()
+                    .next()
+                    .filter(|parent| !parent.comment.deleted && !parent.comment.hidden)
+                    .ok_or(BackendError::NoCommentFound(parent_id))?;
+
+                if parent.comment.depth >= self.config.max_comment_depth {
+                    return Err(BackendError::ValidationError(
+                        format!("comment replies cannot be nested deeper than {}", self.config.max_comment_depth)));
+                }
+                parent.comment.depth + 1
+            },
+            None => 0,
+        };
+
+        let mut comment = db::article::add_comment(self.conn.as_ref().unwrap(), comment_filter, comment_author,
+        comment_body, parent_id, depth).await?;
+
+        // comment is added already, failing to announce it does not undo that
+        match self.record_mentions(comment_author, 
+            &article_response.article, Some(comment.comment.id), comment_body).await {
+            Ok(mentions) => comment.mentions = mentions,
+            Err(err) => eprintln!("recording mentions in comment {} failed: {}", comment.comment.id, err),
+        }
+
+        if let Err(err) = self.notify(&NewNotification {
+            kind: NotificationKind::Comment,
+            recipient: &article_response.article.author,
+            actor: comment_author,
+            article_slug: Some(&article_response.article.slug),
+            comment_id: Some(comment.comment.id),
+        }).await {
+            eprintln!("notifying {} of comment failed: {}", article_response.article.author, err);
+        }
+
+        // article author is addressed directly, unless commenting own article,
+        // viewers of the article pick comment events by slug
+        let recipient = Some(article_response.article.author.as_str())
+            .filter(|author| author != comment_author);
+        if let Err(err) = self.publish(EventKind::Comment, 
+            recipient, 
+            Some(&article_response.article.slug), 
+            json!(comment.clone().wrap()).to_string())
+            .await {
+            eprintln!("publishing comment {} failed: {}", comment.comment.id, err);
+        }
 
-        db::article::add_comment(self.conn.as_ref().unwrap(), comment_filter, comment_author,
-        comment_body).await
+        // comment concerns both the article author and the commenter
+        if let Err(err) = self.dispatch(WebhookEvent::CommentCreated, 
+            &[&article_response.article.author, comment_author], json!({
+            "article": { "slug": &article_response.article.slug },
+            "comment": &comment,
+        })).await {
+            eprintln!("dispatching comment {} failed: {}", comment.comment.id, err);
+        }
+        Ok(comment)
     }
 
     pub async fn delete_comment(&self, 
@@ -267,7 +893,8 @@ impl Server {
             let comments = db::article::get_comments(self.conn.as_ref().unwrap(), 
                                                     filter, 
                                                     filters::OrderByFilter::default(), 
-                                                    filters::LimitOffsetFilter::default().limit(1))
+                                                    filters::LimitOffsetFilter::default().limit(1),
+                                                    None)
                 .await; 
 
             match comments {
@@ -289,18 +916,741 @@ impl Server {
         }
     }
 
-    pub async fn get_comments(&self, slug: &str) -> Result<MultipleCommentResponse, BackendError> {
+    pub async fn restore_comment(&self, 
+        token: &str, 
+        slug: &str,
+        id: i32,
+    ) -> Result<CommentResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let conn = self.conn.as_ref().unwrap();
+
+        let query_res = db::article::restore_comment(conn, 
+            id, slug, &claims.username, self.config.restore_window_minutes).await?;
+        if 0 == query_res.rows_affected() {
+            return Err(BackendError::NoCommentFound(id));
+        }
+        let filter = filters::CommentFilterByValues::default().id(id);
+        db::article::get_comments(conn, 
+                                filter, 
+                                filters::OrderByFilter::default(), 
+                                filters::LimitOffsetFilter::default().limit(1),
+                                Some(&claims.username))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoCommentFound(id))
+    }
+
+    pub async fn refresh_trending(&self) -> Result<(), BackendError> {
+        db::article::refresh_trending(self.conn.as_ref().unwrap(), 
+            self.config.trending_window_hours, TRENDING_DECAY_HOURS).await
+    }
+
+    // removes single views no longer needed for deduplication or trending,
+    // returns number of removed views
+    pub async fn prune_views(&self) -> Result<u64, BackendError> {
+        let keep_minutes = self.config.view_dedup_minutes.max(self.config.trending_window_hours * 60);
+        db::article::prune_views(self.conn.as_ref().unwrap(), keep_minutes).await
+    }
+
+    // permanently removes content deleted longer than restore window ago,
+    // returns number of purged articles and comments
+    pub async fn purge_deleted(&self) -> Result<u64, BackendError> {
+        db::article::purge_deleted(self.conn.as_ref().unwrap(), self.config.restore_window_minutes).await
+    }
+
+    // reverse proxies allowed to pass client address in X-Forwarded-For header
+    pub fn trusts_proxy(&self, addr: &str) -> bool {
+        self.config.trusted_proxies.iter().any(|proxy| proxy == addr)
+    }
+
+    // largest upload request body accepted, file plus room for multipart headers
+    pub fn upload_size_limit(&self) -> u64 {
+        self.config.upload_max_bytes as u64 + 16 * 1024
+    }
+
+    fn ensure_upload_size(&self, upload_req: &requests::upload::UploadRequest) -> Result<(), BackendError> {
+        if self.config.upload_max_bytes < upload_req.data.len() as i64 {
+            Err(BackendError::UploadTooLarge(
+                format!("file exceeds {} bytes", self.config.upload_max_bytes)))
+        } else {
+            Ok(())
+        }
+    }
+
+    // file is stored under the hash of its content, 
+    // so identical uploads share one stored file
+    async fn store_upload(&self, 
+        username: &str, 
+        content_type: &str, 
+        data: Vec<u8>,
+    ) -> Result<Upload, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+        let size = data.len() as i64;
+
+        let (id, data) = async_std::task::spawn_blocking(move || 
+            (hex::encode(sha2::Sha256::digest(&data)), data)).await;
+
+        // recorded first, so that purging orphans never removes the file being stored
+        let upload = db::upload::add_upload(conn, &id, username, content_type, size, 
+            self.config.upload_quota_bytes).await?;
+        self.storage.put(&id, &data).await?;
+        Ok(upload)
+    }
+
+    pub async fn upload(&self, 
+        token: &str, 
+        upload_req: requests::upload::UploadRequest,
+    ) -> Result<Upload, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        self.ensure_not_suspended(&claims.username).await?;
+
+        let content_type = upload_req.content_type()
+            .ok_or(BackendError::ValidationError("unsupported file type".to_string()))?;
+        self.ensure_upload_size(&upload_req)?;
+
+        self.store_upload(&claims.username, content_type, upload_req.data).await
+    }
+
+    // image is stored in all avatar sizes, the largest one becomes profile image
+    pub async fn set_avatar(&self, 
+        token: &str, 
+        upload_req: requests::upload::UploadRequest,
+    ) -> Result<LoggedInUser, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        self.ensure_not_suspended(&claims.username).await?;
+        self.ensure_upload_size(&upload_req)?;
+
+        let data = upload_req.data;
+        let avatars = async_std::task::spawn_blocking(move || avatar::process(&data)).await?;
+        let mut sizes = Vec::with_capacity(avatar::AVATAR_SIZES.len());
+        for (size, png) in avatars {
+            let upload = self.store_upload(&claims.username, "image/png", png).await?;
+            sizes.push((size, upload.id));
+        }
+        db::upload::set_avatar(self.conn.as_ref().unwrap(), &claims.username, &sizes).await?;
+
+        let update_user_req = requests::user::UserUpdateRequest {
+            image: sizes.first().map(|(_, id)| upload::upload_url(id)),
+            ..Default::default()
+        };
+        self.update_user(token, update_user_req).await
+    }
+
+    // processed avatar is served in requested size as long as it remains 
+    // the profile image, users without any image get their identicon
+    pub async fn avatar(&self, 
+        username: &str, 
+        filter: filters::AvatarFilter,
+    ) -> Result<Avatar, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+        let size = filter.size
+            .unwrap_or(avatar::AVATAR_SIZES[0])
+            .clamp(avatar::AVATAR_MIN_SIZE, avatar::AVATAR_MAX_SIZE);
+
+        let image = match db::user::get_image(conn, username).await? {
+            Some(image) => image,
+            None => return Ok(Avatar::Generated(avatar::identicon(username, size)?)),
+        };
+        let largest = db::upload::get_avatar(conn, username, avatar::AVATAR_MAX_SIZE).await?;
+
+        if largest.is_some_and(|upload_id| upload::upload_url(&upload_id) == image) {
+            let upload_id = db::upload::get_avatar(conn, username, size).await?
+                .ok_or(BackendError::NoUploadFound(image))?;
+            Ok(Avatar::Url(upload::upload_url(&upload_id)))
+        } else {
+            Ok(Avatar::Url(image))
+        }
+    }
+
+    pub async fn get_upload(&self, id: &str) -> Result<(Upload, Vec<u8>), BackendError> {
+        let upload = db::upload::get_upload(self.conn.as_ref().unwrap(), id).await?
+            .ok_or(BackendError::NoUploadFound(id.to_string()))?;
+        let data = self.storage.get(&upload.id).await?;
+        Ok((upload, data))
+    }
+
+    // removes files nothing has linked to since they were uploaded
+    pub async fn purge_orphan_uploads(&self) -> Result<u64, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+        let window = self.config.orphan_upload_minutes;
+        let orphans = db::upload::orphan_uploads(conn, window).await?;
+
+        let mut purged = 0;
+        for (id, username) in orphans.iter() {
+            // checked again and the file removed while holding the write lock, 
+            // so that an upload of the same file meanwhile keeps it
+            let mut tx = conn.begin().await?;
+            if db::upload::delete_orphan(&mut tx, id, username, window).await? {
+                self.storage.delete(id).await?;
+                purged += 1;
+            }
+            tx.commit().await?;
+        }
+        Ok(purged)
+    }
+
+    pub async fn update_comment(&self, 
+        token: &str, 
+        update_req: requests::article::UpdateCommentRequest<'_>,
+    ) -> Result<CommentResponse, BackendError> {
+ 
+        let id = update_req.id;
+
+        let update_req_auth = &update_req.authenticate(token, self.secret())?;
+        self.ensure_not_suspended(&update_req_auth.author).await?;
+        let filter = filters::CommentFilterByValues::from(update_req_auth);
+
+        let query_res = db::article::update_comment(self.conn.as_ref().unwrap(), 
+                                                    filter, 
+                                                    &update_req_auth.article_request.body).await?;
+
+        let filter = filters::CommentFilterByValues::default()
+            .id(id)
+            .article_slug(update_req_auth.article_request.article_slug);
+
+        let comment = db::article::get_comments(self.conn.as_ref().unwrap(), 
+                                                filter, 
+                                                filters::OrderByFilter::default(), 
+                                                filters::LimitOffsetFilter::default().limit(1),
+                                                Some(&update_req_auth.author))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoCommentFound(id))?;
+        // if no comment has been updated, check if user is authorized to do so    
+        if 0 == query_res.rows_affected() {
+            auth::Auth::authorize(token, self.secret(), &comment.comment.author)?;
+
+            if comment.comment.deleted {
+                Err(BackendError::NoCommentFound(id))
+            } else {
+                Err(BackendError::UnexpectedError(
+                    "could not update comment despite user has been authorized to, probably due to a bug".to_string()))
+            }
+        } else {
+            Ok(comment)
+        }
+    }
+
+    pub async fn get_comments(&self, 
+        token: Option<&str>,
+        slug: &str,
+        thread_filter: filters::CommentThreadFilter,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleCommentResponse, BackendError> {
+        let viewer = self.viewer(token);
         let filter = filters::CommentFilterByValues::default().article_slug(slug);
-        let order_by = filters::OrderByFilter::Descending("id");
-        let limit_offset: filters::LimitOffsetFilter = filters::LimitOffsetFilter::default();
+        let limit_offset = filters::LimitOffsetFilter {
+            limit: Some(limit_offset.limit.unwrap_or(COMMENT_THREADS).clamp(1, COMMENT_THREADS_MAX)),
+            ..limit_offset
+        };
     
-        let comments = db::article::get_comments(
-            self.conn.as_ref().unwrap(), filter, order_by, limit_offset)
+        let comments = db::article::get_comment_threads(
+            self.conn.as_ref().unwrap(), filter, thread_filter, limit_offset, 
+            self.config.max_thread_replies, viewer.as_deref())
             .await?;
-        Ok(MultipleCommentResponse { comments } )
+
+        let filter = filters::CommentFilterByValues::default().article_slug(slug);
+        let visibility = filters::VisibilityFilter::new(viewer.as_deref(), "author")
+            .exclude_hidden("hidden");
+        let comments_count = db::article::count_comments(self.conn.as_ref().unwrap(), filter, visibility).await?;
+
+        Ok(MultipleCommentResponse::from_comments_threaded(comments, comments_count))
+    }
+
+    pub async fn react_to_article(&self, 
+        token: &str, 
+        slug: &str, 
+        kind: ReactionKind,
+    ) -> Result<ArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        self.visible_article(slug, Some(&claims.username)).await?;
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+
+        db::reaction::add_reaction(self.conn.as_ref().unwrap(), 
+            db::reaction::ReactionTarget::Article, filter, &claims.username, kind).await?;
+
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+        db::article::get_one(self.conn.as_ref().unwrap(), filter, Some(&claims.username)).await
+    }
+
+    pub async fn unreact_to_article(&self, 
+        token: &str, 
+        slug: &str, 
+        kind: ReactionKind,
+    ) -> Result<ArticleResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+
+        db::reaction::remove_reaction(self.conn.as_ref().unwrap(), 
+            db::reaction::ReactionTarget::Article, filter, &claims.username, kind).await?;
+
+        let filter = filters::ArticleFilterByValues::default().slug(slug.to_string());
+        db::article::get_one(self.conn.as_ref().unwrap(), filter, Some(&claims.username)).await
+    }
+
+    pub async fn react_to_comment(&self, 
+        token: &str, 
+        slug: &str, 
+        id: i32,
+        kind: ReactionKind,
+    ) -> Result<CommentResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let comment = self.comment(slug, id, &claims.username).await?;
+        if comment.comment.hidden && !self.can_see_hidden(Some(&claims.username), &comment.comment.author) {
+            return Err(BackendError::Forbidden);
+        }
+        let filter = filters::CommentFilterByValues::default().id(id).article_slug(slug);
+
+        db::reaction::add_reaction(self.conn.as_ref().unwrap(), 
+            db::reaction::ReactionTarget::Comment, filter, &claims.username, kind).await?;
+
+        self.comment(slug, id, &claims.username).await
+    }
+
+    pub async fn unreact_to_comment(&self, 
+        token: &str, 
+        slug: &str, 
+        id: i32,
+        kind: ReactionKind,
+    ) -> Result<CommentResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::CommentFilterByValues::default().id(id).article_slug(slug);
+
+        db::reaction::remove_reaction(self.conn.as_ref().unwrap(), 
+            db::reaction::ReactionTarget::Comment, filter, &claims.username, kind).await?;
+
+        self.comment(slug, id, &claims.username).await
+    }
+
+    async fn comment(&self, slug: &str, id: i32, viewer: &str) -> Result<CommentResponse, BackendError> {
+        let filter = filters::CommentFilterByValues::default().id(id).article_slug(slug);
+
+        db::article::get_comments(self.conn.as_ref().unwrap(), 
+                                    filter, 
+                                    filters::OrderByFilter::default(), 
+                                    filters::LimitOffsetFilter::default().limit(1),
+                                    Some(viewer))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoCommentFound(id))
+    }
+
+    pub async fn get_notifications(&self, 
+        token: &str, 
+        filter: filters::NotificationFilter,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleNotificationResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filter.recipient(claims.username.clone());
+
+        let notifications = db::notification::get_notifications(self.conn.as_ref().unwrap(), 
+            &filter, limit_offset).await?;
+        let notifications_count = db::notification::count_notifications(self.conn.as_ref().unwrap(), 
+            &filter).await?;
+        let unread_count = self.unread_notifications_count(token).await?.unread_count;
+
+        Ok(MultipleNotificationResponse {
+            notifications,
+            notifications_count,
+            unread_count,
+        })
+    }
+
+    pub async fn unread_notifications_count(&self, token: &str) -> Result<UnreadNotificationsCount, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::NotificationFilter::default()
+            .recipient(claims.username)
+            .unread(true);
+
+        let unread_count = db::notification::count_notifications(self.conn.as_ref().unwrap(), 
+            &filter).await?;
+        Ok(UnreadNotificationsCount { unread_count })
+    }
+
+    pub async fn mark_notification_read(&self, token: &str, id: i32) -> Result<NotificationResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::NotificationFilter::default()
+            .recipient(claims.username)
+            .id(id);
+
+        db::notification::mark_read(self.conn.as_ref().unwrap(), &filter).await?;
+
+        db::notification::get_notifications(self.conn.as_ref().unwrap(), 
+            &filter, filters::LimitOffsetFilter::default().limit(1))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoNotificationFound(id))
+    }
+
+    pub async fn mark_all_notifications_read(&self, token: &str) -> Result<UnreadNotificationsCount, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::NotificationFilter::default().recipient(claims.username);
+
+        db::notification::mark_read(self.conn.as_ref().unwrap(), &filter).await?;
+
+        self.unread_notifications_count(token).await
+    }
+
+    pub async fn notification_preferences(&self, token: &str) -> Result<NotificationPreferences, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::notification::get_preferences(self.conn.as_ref().unwrap(), &claims.username).await
+    }
+
+    pub async fn update_notification_preferences(&self, 
+        token: &str, 
+        preferences: NotificationPreferences,
+    ) -> Result<NotificationPreferences, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        if preferences.muted.iter().any(|kind| !kind.can_be_muted()) {
+            return Err(BackendError::ValidationError("moderator warnings cannot be muted".to_string()));
+        }
+
+        db::notification::set_preferences(self.conn.as_ref().unwrap(), &claims.username, &preferences).await
     }
 
     pub async fn get_tags(&self) -> Result<TagList, BackendError> {
         db::article::get_tags(self.conn.as_ref().unwrap()).await 
     }   
+
+    pub async fn tag_cloud(&self, 
+        filter: filters::TagCloudFilter,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<TagCloud, BackendError> {
+        db::tag::get_tag_cloud(self.conn.as_ref().unwrap(), filter, limit_offset).await 
+    }
+
+    // suggests most used tags starting with prefix
+    pub async fn autocomplete_tags(&self, 
+        prefix: &str,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<TagList, BackendError> {
+        let prefix = tag::normalize(prefix);
+        let limit = limit_offset.limit.unwrap_or(TAG_SUGGESTIONS).clamp(1, TAG_SUGGESTIONS_MAX);
+
+        let tags = db::tag::autocomplete(self.conn.as_ref().unwrap(), &prefix, limit).await?;
+        Ok(TagList { tags })
+    }
+
+    // normalized name of the tag the given one is alias of, or of the given one itself
+    async fn canonical_tag(&self, tag: &str) -> Result<String, BackendError> {
+        let tag = Self::valid_tag(tag)?;
+        db::tag::canonical(self.conn.as_ref().unwrap(), &tag).await
+    }
+
+    fn valid_tag(tag: &str) -> Result<String, BackendError> {
+        let tag = tag::normalize(tag);
+        if tag.is_empty() || tag.contains(',') {
+            return Err(BackendError::ValidationError(format!("invalid tag '{}'", tag)));
+        }
+        Ok(tag)
+    }
+
+    // returns tags followed by the user after following the given one
+    pub async fn follow_tag(&self, token: &str, tag: &str) -> Result<TagList, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let tag = self.canonical_tag(tag).await?;
+        db::article::follow_tag(self.conn.as_ref().unwrap(), &claims.username, &tag).await?;
+
+        db::article::get_followed_tags(self.conn.as_ref().unwrap(), &claims.username).await
+    }
+
+    pub async fn unfollow_tag(&self, token: &str, tag: &str) -> Result<TagList, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let tag = self.canonical_tag(tag).await?;
+        db::article::unfollow_tag(self.conn.as_ref().unwrap(), &claims.username, &tag).await?;
+
+        db::article::get_followed_tags(self.conn.as_ref().unwrap(), &claims.username).await
+    }
+
+    pub async fn followed_tags(&self, token: &str) -> Result<TagList, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+
+        db::article::get_followed_tags(self.conn.as_ref().unwrap(), &claims.username).await
+    }
+
+    // tag is served by any of its aliases as well
+    pub async fn get_tag(&self, name: &str) -> Result<Tag, BackendError> {
+        let name = self.canonical_tag(name).await?;
+
+        db::tag::get_tag(self.conn.as_ref().unwrap(), &name)
+            .await?
+            .ok_or(BackendError::NoTagFound(name))
+    }
+
+    pub async fn update_tag(&self, 
+        token: &str, 
+        name: &str, 
+        update_req: requests::tag::UpdateTagRequest,
+    ) -> Result<Tag, BackendError> {
+        self.moderator(token)?;
+        update_req.validate()?;
+        let name = self.canonical_tag(name).await?;
+        let description = update_req.description.as_deref()
+            .map(|description| description.trim())
+            .filter(|description| !description.is_empty());
+
+        db::tag::set_description(self.conn.as_ref().unwrap(), &name, description).await?;
+        self.get_tag(&name).await
+    }
+
+    // aliases are resolved to their tag when articles are written or queried,
+    // tags already in use have to be merged instead
+    pub async fn add_tag_alias(&self, 
+        token: &str, 
+        name: &str, 
+        alias_req: requests::tag::TagAliasRequest,
+    ) -> Result<Tag, BackendError> {
+        self.moderator(token)?;
+        let name = Self::valid_tag(name)?;
+        let alias = Self::valid_tag(&alias_req.name)?;
+
+        let name = db::tag::add_alias(self.conn.as_ref().unwrap(), &alias, &name).await?;
+        self.get_tag(&name).await
+    }
+
+    pub async fn remove_tag_alias(&self, 
+        token: &str, 
+        name: &str, 
+        alias: &str,
+    ) -> Result<Tag, BackendError> {
+        self.moderator(token)?;
+        let name = self.canonical_tag(name).await?;
+        let alias = tag::normalize(alias);
+
+        let query_res = db::tag::remove_alias(self.conn.as_ref().unwrap(), &alias, &name).await?;
+        if 0 == query_res.rows_affected() {
+            return Err(BackendError::NoTagFound(alias));
+        }
+        self.get_tag(&name).await
+    }
+
+    // retags articles carrying the tag and keeps the tag as alias of the one merged into
+    pub async fn merge_tags(&self, 
+        token: &str, 
+        name: &str, 
+        merge_req: requests::tag::MergeTagRequest,
+    ) -> Result<Tag, BackendError> {
+        self.moderator(token)?;
+        let from = Self::valid_tag(name)?;
+        let into = Self::valid_tag(&merge_req.into)?;
+
+        let into = db::tag::merge(self.conn.as_ref().unwrap(), &from, &into).await?;
+        self.get_tag(&into).await
+    }
+
+    pub async fn report_article(&self, 
+        token: &str, 
+        slug: &str,
+        report_req: requests::report::ReportRequest,
+    ) -> Result<Report, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        report_req.validate()?;
+        self.visible_article(slug, Some(&claims.username)).await?;
+
+        db::report::create_report(self.conn.as_ref().unwrap(), &claims.username, slug, None, &report_req.reason).await
+    }
+
+    pub async fn report_comment(&self, 
+        token: &str, 
+        slug: &str,
+        id: i32,
+        report_req: requests::report::ReportRequest,
+    ) -> Result<Report, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        report_req.validate()?;
+        self.visible_article(slug, Some(&claims.username)).await?;
+
+        let comment = self.comment(slug, id, &claims.username).await?;
+        if comment.comment.deleted {
+            return Err(BackendError::NoCommentFound(id));
+        }
+
+        db::report::create_report(self.conn.as_ref().unwrap(), &claims.username, slug, Some(id), &report_req.reason).await
+    }
+
+    pub async fn get_reports(&self, 
+        token: &str, 
+        filter: filters::ReportFilter,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleReportResponse, BackendError> {
+        self.moderator(token)?;
+
+        let reports = db::report::get_reports(self.conn.as_ref().unwrap(), &filter, limit_offset).await?;
+        let reports_count = db::report::count_reports(self.conn.as_ref().unwrap(), &filter).await?;
+
+        Ok(MultipleReportResponse { reports, reports_count })
+    }
+
+    pub async fn dismiss_report(&self, token: &str, id: i32) -> Result<Report, BackendError> {
+        let claims = self.moderator(token)?;
+        let report = self.open_report(id).await?;
+
+        self.resolve_report(report, ReportStatus::Dismissed, None, &claims.username).await
+    }
+
+    pub async fn moderate_report(&self, 
+        token: &str, 
+        id: i32,
+        moderation_req: requests::report::ModerationRequest,
+    ) -> Result<Report, BackendError> {
+        let claims = self.moderator(token)?;
+        let report = self.open_report(id).await?;
+
+        let report = self.resolve_report(report, ReportStatus::Actioned, Some(moderation_req.action), &claims.username).await?;
+        // warning is sent only by the moderator who has resolved the report
+        if moderation_req.action == ModerationAction::Warn {
+            if let Err(err) = self.notify(&NewNotification {
+                kind: NotificationKind::Warning,
+                recipient: &report.author,
+                actor: &claims.username,
+                article_slug: Some(&report.article_slug),
+                comment_id: report.comment_id,
+            }).await {
+                eprintln!("warning {} failed: {}", report.author, err);
+            }
+        }
+        Ok(report)
+    }
+
+    async fn open_report(&self, id: i32) -> Result<Report, BackendError> {
+        let filter = filters::ReportFilter::default().id(id);
+        let report = db::report::get_reports(self.conn.as_ref().unwrap(), &filter, filters::LimitOffsetFilter::default())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoReportFound(id))?;
+
+        if report.status != ReportStatus::Open {
+            return Err(BackendError::ValidationError(format!("report {} has already been resolved", id)));
+        }
+        Ok(report)
+    }
+
+    async fn resolve_report(&self, 
+        report: Report, 
+        status: ReportStatus, 
+        action: Option<ModerationAction>,
+        moderator: &str,
+    ) -> Result<Report, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+
+        if 0 == db::report::resolve_report(conn, &report, status, action, moderator).await? {
+            return Err(BackendError::ValidationError(format!("report {} has already been resolved", report.id)));
+        }
+
+        let filter = filters::ReportFilter::default().id(report.id);
+        db::report::get_reports(conn, &filter, filters::LimitOffsetFilter::default())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(BackendError::NoReportFound(report.id))
+    }
+
+    pub async fn register_webhook(&self, 
+        token: &str, 
+        webhook_req: requests::webhook::CreateWebhookRequest
+    ) -> Result<WebhookResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        webhook_req.validate()?;
+        if !self.config.webhook_allow_private {
+            webhooks::ensure_public(&webhook_req.url)
+                .await
+                .map_err(BackendError::ValidationError)?;
+        }
+
+        db::webhook::create_webhook(self.conn.as_ref().unwrap(), 
+            &claims.username, 
+            &webhook_req.url, 
+            &webhook_req.secret, 
+            &webhook_req.events)
+        .await
+    }
+
+    pub async fn get_webhooks(&self, token: &str) -> Result<MultipleWebhookResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::WebhookFilter::default().owner(claims.username);
+
+        let webhooks = db::webhook::get_webhooks(self.conn.as_ref().unwrap(), &filter).await?;
+        Ok(MultipleWebhookResponse { webhooks })
+    }
+
+    pub async fn delete_webhook(&self, token: &str, id: i32) -> Result<(), BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::WebhookFilter::default()
+            .owner(claims.username)
+            .id(id);
+
+        let query_res = db::webhook::delete_webhook(self.conn.as_ref().unwrap(), &filter).await?;
+        if 0 == query_res.rows_affected() {
+            Err(BackendError::NoWebhookFound(id))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn webhook_deliveries(&self, 
+        token: &str, 
+        id: i32,
+        limit_offset: filters::LimitOffsetFilter,
+    ) -> Result<MultipleWebhookDeliveryResponse, BackendError> {
+        let claims = auth::Auth::authenticate(token, self.secret())?;
+        let filter = filters::WebhookFilter::default()
+            .owner(claims.username)
+            .id(id);
+        // webhooks of other users are not revealed
+        if db::webhook::get_webhooks(self.conn.as_ref().unwrap(), &filter).await?.is_empty() {
+            return Err(BackendError::NoWebhookFound(id));
+        }
+
+        let deliveries = db::webhook::get_deliveries(self.conn.as_ref().unwrap(), id, limit_offset).await?;
+        let deliveries_count = db::webhook::count_deliveries(self.conn.as_ref().unwrap(), id).await?;
+
+        Ok(MultipleWebhookDeliveryResponse { deliveries, deliveries_count })
+    }
+
+    // attempts deliveries which are due, returns number of successful ones,
+    // failed attempts are rescheduled with exponential backoff
+    pub async fn deliver_webhooks(&self) -> Result<usize, BackendError> {
+        let conn = self.conn.as_ref().unwrap();
+        let mut delivered = 0;
+
+        // the batch is posted concurrently, outcomes are recorded afterwards
+        let allow_private = self.config.webhook_allow_private;
+        let attempts = db::webhook::due_deliveries(conn, WEBHOOK_DELIVERY_BATCH)
+            .await?
+            .into_iter()
+            .map(|delivery| async_std::task::spawn(async move {
+                let res = webhooks::deliver(&delivery, allow_private).await;
+                (delivery, res)
+            }))
+            .collect::<Vec<_>>();
+
+        for attempt in attempts {
+            let (delivery, res) = attempt.await;
+            match res {
+                Ok(response_status) => {
+                    db::webhook::delivery_succeeded(conn, delivery.id, response_status).await?;
+                    delivered += 1;
+                },
+                Err(err) => {
+                    let attempts = delivery.attempts + 1;
+                    let retry_in_seconds = (attempts < self.config.webhook_max_attempts)
+                        .then(|| webhooks::backoff_seconds(self.config.webhook_backoff_seconds, attempts - 1));
+
+                    db::webhook::delivery_failed(conn, 
+                        delivery.id, 
+                        err.response_status, 
+                        &err.message, 
+                        retry_in_seconds)
+                    .await?;
+                },
+            }
+        }
+        Ok(delivered)
+    }
 }
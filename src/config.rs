@@ -5,6 +5,24 @@ pub struct Config {
     pub database_file: String,
     pub secret: String,
     pub drop_database: bool,
+    pub max_comment_depth: i32,
+    pub max_thread_replies: i64,
+    pub event_buffer_minutes: i64,
+    pub webhook_max_attempts: i32,
+    pub webhook_backoff_seconds: i64,
+    pub webhook_poll_seconds: u64,
+    pub webhook_allow_private: bool,
+    pub moderators: Vec<String>,
+    pub restore_window_minutes: i64,
+    pub purge_poll_seconds: u64,
+    pub trending_window_hours: i64,
+    pub trending_refresh_seconds: u64,
+    pub view_dedup_minutes: i64,
+    pub trusted_proxies: Vec<String>,
+    pub upload_dir: String,
+    pub upload_max_bytes: i64,
+    pub upload_quota_bytes: i64,
+    pub orphan_upload_minutes: i64,
 }
 
 impl Config {
@@ -20,6 +38,83 @@ impl Config {
                 .ok()
                 .and_then(|s| s.parse::<u32>().ok() )
                 .unwrap_or(0),
+            max_comment_depth: std::env::var("MAX_COMMENT_DEPTH")
+                .ok()
+                .and_then(|s| s.parse::<i32>().ok() )
+                .unwrap_or(5),
+            max_thread_replies: std::env::var("MAX_THREAD_REPLIES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(200),
+            event_buffer_minutes: std::env::var("EVENT_BUFFER_MINUTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(10),
+            webhook_max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|s| s.parse::<i32>().ok() )
+                .unwrap_or(5),
+            webhook_backoff_seconds: std::env::var("WEBHOOK_BACKOFF_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(30),
+            webhook_poll_seconds: std::env::var("WEBHOOK_POLL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok() )
+                .unwrap_or(5),
+            webhook_allow_private: 0 != std::env::var("WEBHOOK_ALLOW_PRIVATE")
+                .ok()
+                .and_then(|s| s.parse::<u32>().ok() )
+                .unwrap_or(0),
+            moderators: std::env::var("MODERATORS")
+                .ok()
+                .map(|s| s.split(',')
+                    .map(|username| username.trim().to_string())
+                    .filter(|username| !username.is_empty())
+                    .collect())
+                .unwrap_or_default(),
+            restore_window_minutes: std::env::var("RESTORE_WINDOW_MINUTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(1440),
+            purge_poll_seconds: std::env::var("PURGE_POLL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok() )
+                .unwrap_or(3600),
+            trending_window_hours: std::env::var("TRENDING_WINDOW_HOURS")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(72),
+            trending_refresh_seconds: std::env::var("TRENDING_REFRESH_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok() )
+                .unwrap_or(300),
+            view_dedup_minutes: std::env::var("VIEW_DEDUP_MINUTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(30),
+            trusted_proxies: std::env::var("TRUSTED_PROXIES")
+                .ok()
+                .map(|s| s.split(',')
+                    .map(|proxy| proxy.trim().to_string())
+                    .filter(|proxy| !proxy.is_empty())
+                    .collect())
+                .unwrap_or_default(),
+            upload_dir: std::env::var("UPLOAD_DIR")
+                .ok()
+                .unwrap_or("./uploads/".to_string()),
+            upload_max_bytes: std::env::var("UPLOAD_MAX_BYTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(5 * 1024 * 1024),
+            upload_quota_bytes: std::env::var("UPLOAD_QUOTA_BYTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(50 * 1024 * 1024),
+            orphan_upload_minutes: std::env::var("ORPHAN_UPLOAD_MINUTES")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok() )
+                .unwrap_or(1440),
         }
     }
 }
@@ -1,13 +1,22 @@
-use sqlx::{Pool};
+use sqlx::{Pool, Transaction};
 use sqlx::sqlite::{Sqlite, SqliteQueryResult};
 use crate::{models::article, filters, errors};
 
 pub(crate) async fn create_article(conn: &Pool<Sqlite>,
-    article: article::Article,
+    mut article: article::Article,
 ) -> Result<article::ArticleResponse, errors::BackendError>  {
-    sqlx::query(
-        "INSERT INTO articles (author, slug, title, description, body, tagList, createdAt, updatedAt)
-        VALUES( ?,	?, ?, ?, ?, ?, datetime('now'), datetime('now'));
+    // slug is picked while holding the write lock, 
+    // so that concurrent creates wait instead of racing into the unique index
+    let mut tx = super::begin_immediate(conn).await?;
+    article.slug = unique_slug(&mut tx, &article.slug, None).await?;
+    if let Some(tag_list) = article.tag_list.as_ref() {
+        article.tag_list = Some(super::tag::canonical_tag_list(&mut tx, tag_list).await?);
+    }
+
+    let query_res = sqlx::query(
+        "INSERT INTO articles (author, slug, title, description, body, tagList, createdAt, updatedAt,
+            wordCount, readingTimeMinutes, generatedDescription)
+        VALUES( ?,	?, ?, ?, ?, ?, datetime('now'), datetime('now'), ?, ?, ?);
         ")
         .bind(&article.author)
         .bind(&article.slug)
@@ -15,42 +24,113 @@ pub(crate) async fn create_article(conn: &Pool<Sqlite>,
         .bind(&article.description)
         .bind(&article.body)
         .bind(&article.tag_list)
-        .execute(conn)    
+        .bind(article.word_count)
+        .bind(article.reading_time_minutes)
+        .bind(article.generated_description)
+        .execute(&mut tx)    
         .await?;
 
+    if let Some(tag_list) = article.tag_list.as_ref() {
+        super::tag::index_article_tags(&mut tx, query_res.last_insert_rowid(), tag_list).await?;
+    }
+    tx.commit().await?;
+
     let article = get_one(conn, 
-        filters::ArticleFilterByValues::default().slug(article.slug.to_string())
+        filters::ArticleFilterByValues::default().slug(article.slug.to_string()),
+        Some(&article.author),
     ).await?;
     Ok(article)
 }
 
+// makes slug unique by suffixing it with the first free counter, 
+// slugs kept in history of other articles are considered taken as well,
+// so that their redirects keep working
+pub(crate) async fn unique_slug(tx: &mut Transaction<'_, Sqlite>,
+    slug: &str,
+    article_id: Option<i32>,
+) -> Result<String, errors::BackendError>  {
+    let slug = if slug.is_empty() { "article" } else { slug };
+    // AUTOINCREMENT ids start from 1, so 0 excludes nothing
+    let article_id = article_id.unwrap_or(0);
+
+    let taken: std::collections::HashSet<String> = sqlx::query_scalar(
+        "SELECT slug FROM articles WHERE id<>? AND (slug=? OR slug LIKE ? || '-%')
+        UNION 
+        SELECT slug FROM slug_history WHERE article_id<>? AND (slug=? OR slug LIKE ? || '-%');
+        ")
+        .bind(article_id)
+        .bind(slug)
+        .bind(slug)
+        .bind(article_id)
+        .bind(slug)
+        .bind(slug)
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .collect();
+
+    if !taken.contains(slug) {
+        return Ok(slug.to_string());
+    }
+    Ok((2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap())
+}
+
+// returns current slug of article that used to be reachable by the given one
+pub(crate) async fn slug_alias(conn: &Pool<Sqlite>,
+    old_slug: &str,
+) -> Result<Option<String>, errors::BackendError>  {
+    let slug = sqlx::query_scalar(
+        "SELECT articles.slug FROM slug_history 
+        INNER JOIN articles ON articles.id = slug_history.article_id 
+        WHERE slug_history.slug=? AND articles.deletedAt IS NULL;
+        ")
+        .bind(old_slug)
+        .fetch_optional(conn)
+        .await?;
+    Ok(slug)
+}
+
+// deleted comment is shown only as a tombstone of a thread
+const NOT_DELETED_COMMENT: &str = "(comments.deletedAt IS NULL OR comments.deleted=1)";
+
 fn get_article_clause<F: crate::filters::Filter>(
     filter: &F, 
+    visibility: &crate::filters::VisibilityFilter,
     order_by: &crate::filters::OrderByFilter,
     limit_offset: &crate::filters::LimitOffsetFilter,
 ) -> String  {
     format!(" \
         SELECT *, (favoritesCount>0) as favorited FROM \
             (SELECT articles.id as id, slug, title, body, description, tagList, \
-                createdAt, updatedAt, author, \
-                COUNT(favorite_articles.id) as favoritesCount FROM articles \
+                createdAt, updatedAt, author, version, hidden, \
+                wordCount, readingTimeMinutes, generatedDescription, viewsCount, \
+                COUNT(favorite_articles.id) as favoritesCount \
+                FROM articles {} \
             LEFT JOIN favorite_articles ON articles.id = favorite_articles.id \
-            WHERE {} \
+            WHERE {} AND {} \
             GROUP BY articles.id
             {} {})", 
-        filter, order_by, limit_offset
+        filter.join(), filter, visibility, order_by, limit_offset
     )
 }
 
 pub(crate) async fn get_one<F: crate::filters::Filter>(conn: &Pool<Sqlite>,
     filter: F,
+    viewer: Option<&str>,
 ) -> Result<article::ArticleResponse, errors::BackendError>  {
     let limit_offset = crate::filters::LimitOffsetFilter { 
         limit: Some(1), 
         offset: None 
     };
 
-    let articles = get_articles(conn, filter, crate::filters::OrderByFilter::default(), limit_offset).await?;
+    // article reached directly is served even if its author is muted
+    // or it is hidden, it is up to caller to decide who can see it
+    let visibility = crate::filters::VisibilityFilter::new(None, "author");
+
+    let articles = get_articles(conn, filter, visibility, crate::filters::OrderByFilter::default(), limit_offset, viewer).await?;
     if let Some(article) = articles.into_iter().next() {
         Ok(article)    
     } else {
@@ -61,20 +141,42 @@ pub(crate) async fn get_one<F: crate::filters::Filter>(conn: &Pool<Sqlite>,
 pub(crate) async fn get_all<F: crate::filters::Filter>(conn: &Pool<Sqlite>,
     filter: F,
     order_by: crate::filters::OrderByFilter<'_>,
-    limit_offset: crate::filters::LimitOffsetFilter
+    limit_offset: crate::filters::LimitOffsetFilter,
+    viewer: Option<&str>,
 ) -> Result<Vec<article::ArticleResponse>, errors::BackendError>  {
 
-    get_articles(conn, filter, order_by, limit_offset).await
+    let visibility = crate::filters::VisibilityFilter::new(viewer, "author")
+        .exclude_hidden("articles.hidden");
+
+    get_articles(conn, filter, visibility, order_by, limit_offset, viewer).await
+}
+
+// articles to read next after the given one, leaving out ones 
+// by authors viewer muted, blocked or is blocked by
+pub(crate) async fn get_related(conn: &Pool<Sqlite>,
+    article_id: i32,
+    limit_offset: crate::filters::LimitOffsetFilter,
+    viewer: Option<&str>,
+) -> Result<Vec<article::ArticleResponse>, errors::BackendError>  {
+    let filter = crate::filters::ArticleFilterRelated { article_id };
+    let order_by = crate::filters::OrderByFilter::Descending(crate::filters::ArticleFilterRelated::SCORE);
+    let visibility = crate::filters::VisibilityFilter::new(viewer, "author")
+        .exclude_hidden("articles.hidden")
+        .exclude_blocked();
+
+    get_articles(conn, filter, visibility, order_by, limit_offset, viewer).await
 }
 
 async fn get_articles<F: crate::filters::Filter>(conn: &Pool<Sqlite>,
  //   filter: crate::filters::ArticleFilterEnum<'_>,
     filter: F,
+    visibility: crate::filters::VisibilityFilter<'_>,
     order_by: crate::filters::OrderByFilter<'_>,
-    limit_offset: crate::filters::LimitOffsetFilter
+    limit_offset: crate::filters::LimitOffsetFilter,
+    viewer: Option<&str>,
 ) -> Result<Vec::<article::ArticleResponse>, errors::BackendError>  {
   
-    let statement = get_article_clause(&filter, &order_by, &limit_offset);
+    let statement = get_article_clause(&filter, &visibility, &order_by, &limit_offset);
 
     let articles = sqlx::query_as::<_, article::Article>(
         &statement
@@ -85,9 +187,19 @@ async fn get_articles<F: crate::filters::Filter>(conn: &Pool<Sqlite>,
 
     let mut multiple_articles = Vec::<article::ArticleResponse>::with_capacity(articles.len());
 
+    let ids = articles.iter().map(|article| article.id).collect::<Vec<i32>>();
+    let mut reactions = super::reaction::get_reactions(conn, 
+        super::reaction::ReactionTarget::Article, &ids, viewer).await?;
+
     for article in articles {
         let author = super::user::get_profile(conn, &article.author).await;
-        multiple_articles.push( article::ArticleResponse { article, author } );
+        let reactions = reactions.remove(&article.id).unwrap_or_default();
+        let bookmarked = match viewer {
+            Some(viewer) => is_bookmarked(conn, article.id, viewer).await?,
+            None => false,
+        };
+        let mentions = super::mention::get_mentions(conn, article.id).await?;
+        multiple_articles.push( article::ArticleResponse { article, author, reactions, bookmarked, mentions, body_html: None } );
     }
 
 //    if 0 != multiple_articles.len() {
@@ -103,20 +215,58 @@ pub(crate) async fn update_article(conn: &Pool<Sqlite>,
         filter: crate::filters::UpdateArticleFilter<'_>
 ) -> Result<article::ArticleResponse, errors::BackendError>  {
 
-    let statement = format!("UPDATE articles SET {} WHERE {}", update_article, filter);
+    let mut update_article = update_article.clone();
+    let mut tx = super::begin_immediate(conn).await?;
+
+    // title change produces a new slug, the old one is kept in history
+    // for redirecting to the article
+    if let Some(slug) = update_article.get_slug() {
+        let statement = format!("SELECT id, slug FROM articles WHERE {}", filter);
+        let (id, current_slug) = sqlx::query_as::<_, (i32, String)>(&statement)
+            .fetch_optional(&mut tx)
+            .await?
+            .ok_or(errors::BackendError::NoArticleFound)?;
+
+        let slug = unique_slug(&mut tx, &slug, Some(id)).await?;
+        if slug != current_slug {
+            sqlx::query(
+                "DELETE FROM slug_history WHERE slug=? AND article_id=?;
+                INSERT INTO slug_history (slug, article_id) VALUES(?, ?) ON CONFLICT DO NOTHING;
+                ")
+                .bind(&slug)
+                .bind(id)
+                .bind(&current_slug)
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        update_article.slug = Some(slug);
+    }
+    update_article.text_stats = update_article.get_text_stats();
+    // cleared description is generated from the current body, when it is not changed
+    if update_article.text_stats.is_none() && update_article.clears_description() {
+        let statement = format!("SELECT body FROM articles WHERE {}", filter);
+        let body = sqlx::query_scalar::<_, String>(&statement)
+            .fetch_optional(&mut tx)
+            .await?
+            .ok_or(errors::BackendError::NoArticleFound)?;
+        update_article.text_stats = Some(crate::markdown::TextStats::from_markdown(&body));
+    }
+
+    let statement = format!("UPDATE articles SET {}, version=version+1 WHERE {}", update_article, filter);
     let query_res = sqlx::query(&statement)
-        .execute(conn)    
+        .execute(&mut tx)    
         .await?;
 
     if 0 < query_res.rows_affected() {    
-        let updated_slug = update_article.get_slug();
-        let updated_slug = if let Some(ref slug) = updated_slug {
-            slug
-        } else { filter.slug };
+        tx.commit().await?;
+        let updated_slug = update_article.slug.as_deref().unwrap_or(filter.slug);
 
-        get_one(conn, filters::ArticleFilterByValues::default().slug(updated_slug.to_string()))
+        get_one(conn, filters::ArticleFilterByValues::default().slug(updated_slug.to_string()), Some(filter.author))
             .await
     } else {
+        // slug history is left as it was
+        tx.rollback().await?;
         Err(errors::BackendError::NoArticleFound)
     }
 }
@@ -125,12 +275,29 @@ pub(crate) async fn delete_article(conn: &Pool<Sqlite>,
     filter: crate::filters::UpdateArticleFilter<'_>
 ) -> Result<SqliteQueryResult, sqlx::Error> {
 
-    let statement = format!("DELETE FROM articles WHERE {}", filter);
+    let statement = format!("UPDATE articles SET deletedAt=datetime('now') WHERE {}", filter);
     
     sqlx::query(&statement)
         .execute(conn)    
         .await
 }
+
+// brings back article deleted by the author no longer than window_minutes ago
+pub(crate) async fn restore_article(conn: &Pool<Sqlite>,
+    slug: &str,
+    author: &str,
+    window_minutes: i64,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query(
+        "UPDATE articles SET deletedAt=NULL 
+        WHERE slug=? AND author=? AND deletedAt > datetime('now', ?);
+        ")
+        .bind(slug)
+        .bind(author)
+        .bind(format!("-{} minutes", window_minutes))
+        .execute(conn)    
+        .await
+}
 /*
 pub(crate) async fn get_favorite<F: filters::Filter>(conn: &Pool<Sqlite>,
     filter: F,
@@ -152,7 +319,7 @@ pub(crate) async fn get_favorite<F: filters::Filter>(conn: &Pool<Sqlite>,
 pub(crate) async fn favorite_article(conn: &Pool<Sqlite>,
     slug: &str,
     username: &str,
-) -> Result<article::ArticleResponse, errors::BackendError>  {
+) -> Result<(article::ArticleResponse, u64), errors::BackendError>  {
     let favorite_by = filters::ArticleFilterByValues::default().slug(slug.to_string());
 
     let statement = format!("\
@@ -161,13 +328,15 @@ pub(crate) async fn favorite_article(conn: &Pool<Sqlite>,
             ON CONFLICT DO NOTHING; \
         ", favorite_by, username);
     
-    sqlx::query(
+    let query_res = sqlx::query(
         &statement
     )
     .execute(conn)
     .await?;        
 
-    get_one(conn, favorite_by).await
+    // rows affected tell whether the article has been favorited just now
+    let article = get_one(conn, favorite_by, Some(username)).await?;
+    Ok((article, query_res.rows_affected()))
 }
 
 pub(crate) async fn unfavorite_article(conn: &Pool<Sqlite>,
@@ -185,13 +354,185 @@ pub(crate) async fn unfavorite_article(conn: &Pool<Sqlite>,
         .execute(conn)
         .await?;
     
-    get_one(conn, unfavorite_by).await
+    get_one(conn, unfavorite_by, Some(username)).await
+}
+
+// recomputes trending scores from favorites, comments and views of the last window_hours,
+// every action adds its weight divided by (1 + its age in units of decay_hours)
+pub(crate) async fn refresh_trending(conn: &Pool<Sqlite>,
+    window_hours: i64,
+    decay_hours: f64,
+) -> Result<(), errors::BackendError>  {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM article_scores;
+        INSERT INTO article_scores (article_id, trendingScore) 
+            SELECT article_id, 
+                SUM(weight / (1.0 + (julianday('now') - julianday(createdAt)) * 24.0 / ?)) 
+            FROM (
+                SELECT id AS article_id, 3.0 AS weight, favoritedAt AS createdAt FROM favorite_articles 
+                    WHERE favoritedAt > datetime('now', ?)
+                UNION ALL
+                SELECT article_id, 2.0 AS weight, createdAt FROM comments 
+                    WHERE createdAt > datetime('now', ?) AND deletedAt IS NULL
+                UNION ALL
+                SELECT article_id, 1.0 AS weight, viewedAt AS createdAt FROM article_views 
+                    WHERE viewedAt > datetime('now', ?)
+            ) 
+            GROUP BY article_id;
+        ")
+        .bind(decay_hours)
+        .bind(format!("-{} hours", window_hours))
+        .bind(format!("-{} hours", window_hours))
+        .bind(format!("-{} hours", window_hours))
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// records view unless the viewer has viewed the article within last window_minutes,
+// counted view is added to totals of the article, returns whether the view has been counted
+pub(crate) async fn record_view(conn: &Pool<Sqlite>,
+    article_id: i32,
+    viewer: &str,
+    window_minutes: i64,
+) -> Result<bool, errors::BackendError>  {
+    let mut tx = conn.begin().await?;
+
+    let query_res = sqlx::query(
+        "INSERT INTO article_views (article_id, viewer, viewedAt) 
+            SELECT ?, ?, datetime('now') WHERE NOT EXISTS 
+                (SELECT 1 FROM article_views WHERE article_id=? AND viewer=? AND viewedAt > datetime('now', ?));
+        ")
+        .bind(article_id)
+        .bind(viewer)
+        .bind(article_id)
+        .bind(viewer)
+        .bind(format!("-{} minutes", window_minutes))
+        .execute(&mut tx)
+        .await?;
+    if 0 == query_res.rows_affected() {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "UPDATE articles SET viewsCount=viewsCount+1 WHERE id=?;
+        INSERT INTO article_view_days (article_id, day, views) VALUES(?, date('now'), 1) 
+            ON CONFLICT (article_id, day) DO UPDATE SET views=views+1;
+        ")
+        .bind(article_id)
+        .bind(article_id)
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+// deletes single views older than keep_minutes, their totals are kept,
+// returns number of deleted views
+pub(crate) async fn prune_views(conn: &Pool<Sqlite>,
+    keep_minutes: i64,
+) -> Result<u64, errors::BackendError>  {
+    let query_res = sqlx::query("DELETE FROM article_views WHERE viewedAt <= datetime('now', ?);")
+        .bind(format!("-{} minutes", keep_minutes))
+        .execute(conn)
+        .await?;
+    Ok(query_res.rows_affected())
+}
+
+// body rendered for the given article version, if any
+pub(crate) async fn cached_html(conn: &Pool<Sqlite>,
+    article_id: i32,
+    version: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_as::<_, (String,)>("SELECT bodyHtml FROM article_html WHERE article_id=? AND version=?;")
+        .bind(article_id)
+        .bind(version)
+        .fetch_optional(conn)
+        .await
+        .map(|row| row.map(|(body_html,)| body_html))
+}
+
+// keeps only the rendering of the latest version
+pub(crate) async fn cache_html(conn: &Pool<Sqlite>,
+    article_id: i32,
+    version: i32,
+    body_html: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM article_html WHERE article_id=? AND version<>?;
+        INSERT INTO article_html (article_id, version, bodyHtml) VALUES(?, ?, ?) ON CONFLICT DO NOTHING;
+        ")
+        .bind(article_id)
+        .bind(version)
+        .bind(article_id)
+        .bind(version)
+        .bind(body_html)
+        .execute(conn)
+        .await
+}
+
+pub(crate) async fn bookmark_article(conn: &Pool<Sqlite>,
+    slug: &str,
+    username: &str,
+) -> Result<article::ArticleResponse, errors::BackendError>  {
+    let bookmark_by = filters::ArticleFilterByValues::default().slug(slug.to_string());
+
+    let statement = format!("\
+        INSERT INTO bookmarks (article_id, username) \
+            SELECT id, ? FROM articles WHERE {} \
+            ON CONFLICT DO NOTHING; \
+        ", bookmark_by);
+    
+    sqlx::query(&statement)
+        .bind(username)
+        .execute(conn)
+        .await?;
+
+    get_one(conn, bookmark_by, Some(username)).await
+}
+
+pub(crate) async fn unbookmark_article(conn: &Pool<Sqlite>,
+    slug: &str,
+    username: &str,
+) -> Result<article::ArticleResponse, errors::BackendError>  {
+    let unbookmark_by = filters::ArticleFilterByValues::default().slug(slug.to_string());
+
+    let statement = format!("\
+        DELETE FROM bookmarks WHERE username=? AND \
+            article_id=(SELECT id FROM articles WHERE {}) \
+        ", unbookmark_by);
+    
+    sqlx::query(&statement)
+        .bind(username)
+        .execute(conn)
+        .await?;
+    
+    get_one(conn, unbookmark_by, Some(username)).await
+}
+
+async fn is_bookmarked(conn: &Pool<Sqlite>,
+    article_id: i32,
+    username: &str,
+) -> Result<bool, errors::BackendError>  {
+    let bookmarked = sqlx::query_scalar("SELECT COUNT(*)>0 FROM bookmarks WHERE article_id=? AND username=?")
+        .bind(article_id)
+        .bind(username)
+        .fetch_one(conn)
+        .await?;
+    Ok(bookmarked)
 }
 
 pub(crate) async fn get_comments(conn: &Pool<Sqlite>,
     filter: filters::CommentFilterByValues<'_>,
     order_by: filters::OrderByFilter<'_>,
     limit_filter: crate::filters::LimitOffsetFilter,
+    viewer: Option<&str>,
 ) -> Result< Vec<article::CommentResponse>, errors::BackendError>  {
 
     let statement = format!("SELECT * FROM comments WHERE {} {} {}", filter, order_by, limit_filter);
@@ -200,33 +541,118 @@ pub(crate) async fn get_comments(conn: &Pool<Sqlite>,
         .fetch_all(conn)  
         .await?;
     
+    comment_responses(conn, comments, viewer).await
+}
+
+async fn comment_responses(conn: &Pool<Sqlite>,
+    comments: Vec<article::Comment>,
+    viewer: Option<&str>,
+) -> Result< Vec<article::CommentResponse>, errors::BackendError>  {
     let mut multiple_comments = Vec::<article::CommentResponse>::with_capacity(comments.len());
 
-    for comment in comments {
+    let ids = comments.iter().map(|comment| comment.id).collect::<Vec<i32>>();
+    let mut reactions = super::reaction::get_reactions(conn, 
+        super::reaction::ReactionTarget::Comment, &ids, viewer).await?;
+
+    for mut comment in comments {
+        // text of tombstone is kept until purge only for restoring
+        if comment.deleted {
+            comment.body.clear();
+        }
         let author = super::user::get_profile(conn, &comment.author).await;
-        multiple_comments.push( article::CommentResponse { comment, author } );
+        let reactions = reactions.remove(&comment.id).unwrap_or_default();
+        let mentions = match comment.deleted {
+            true => Vec::new(),
+            false => super::mention::get_comment_mentions(conn, comment.id).await?,
+        };
+        multiple_comments.push( article::CommentResponse { comment, author, reactions, mentions, body_html: None } );
+    }
+    Ok(multiple_comments)
+}
+
+// returns a page of top level comments followed by all their replies,
+// replies are ordered from oldest to newest, comments hidden by moderators
+// or by authors muted by viewer are left out along with replies to them
+pub(crate) async fn get_comment_threads(conn: &Pool<Sqlite>,
+    filter: filters::CommentFilterByValues<'_>,
+    thread_filter: filters::CommentThreadFilter,
+    limit_filter: crate::filters::LimitOffsetFilter,
+    max_replies: i64,
+    viewer: Option<&str>,
+) -> Result< Vec<article::CommentResponse>, errors::BackendError>  {
+    let visibility = filters::VisibilityFilter::new(viewer, "comments.author")
+        .exclude_hidden("comments.hidden");
+
+    let statement = format!("SELECT * FROM comments WHERE {} AND {} AND {} {} {}", 
+        filter, thread_filter, visibility, thread_filter.order_by(), limit_filter);
+
+    let mut comments = sqlx::query_as::<_, article::Comment>(&statement)
+        .fetch_all(conn)  
+        .await?;
+
+    if !comments.is_empty() {
+        let ids = comments.iter()
+            .map(|comment| comment.id.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        // replies are collected shallowest first and the recursion stops at the limit,
+        // every collected reply has its parent collected before
+        let statement = format!("\
+            WITH RECURSIVE thread(id) AS ( \
+                SELECT id FROM comments WHERE parentId IN ({}) AND {} AND {} \
+                UNION ALL \
+                SELECT comments.id FROM comments INNER JOIN thread ON comments.parentId = thread.id \
+                    WHERE {} AND {} \
+                LIMIT {}) \
+            SELECT * FROM comments WHERE id IN thread ORDER BY id ASC", 
+            ids, visibility, NOT_DELETED_COMMENT, visibility, NOT_DELETED_COMMENT, max_replies);
+
+        let replies = sqlx::query_as::<_, article::Comment>(&statement)
+            .fetch_all(conn)  
+            .await?;
+        comments.extend(replies);
     }
-    Ok(multiple_comments)    
+    
+    comment_responses(conn, comments, viewer).await
+}
+
+pub(crate) async fn count_comments(conn: &Pool<Sqlite>,
+    filter: filters::CommentFilterByValues<'_>,
+    visibility: filters::VisibilityFilter<'_>,
+) -> Result<i64, errors::BackendError>  {
+
+    let statement = format!("SELECT COUNT(*) FROM comments WHERE {} AND {} AND deleted=0", filter, visibility);
+
+    let count = sqlx::query_scalar(&statement)
+        .fetch_one(conn)  
+        .await?;
+    Ok(count)
 }
     
 pub(crate) async fn add_comment(conn: &Pool<Sqlite>,
     filter: filters::CommentFilterByValues<'_>,
     author: &str,
     comment_body: &str,
+    parent_id: Option<i32>,
+    depth: i32,
 ) -> Result<article::CommentResponse, errors::BackendError>  {
-    let statement = format!("INSERT INTO comments 
-        (author, body, createdAt, updatedAt, article_id) 
-        VALUES( '{}','{}', datetime('now'), datetime('now'), 
-            (SELECT id FROM articles WHERE slug='{}' LIMIT 1));", 
-        author, comment_body, filter.article_slug.unwrap());
-    sqlx::query(&statement)
+    sqlx::query("INSERT INTO comments 
+        (author, body, createdAt, updatedAt, article_id, parentId, depth) 
+        VALUES( ?, ?, datetime('now'), datetime('now'), 
+            (SELECT id FROM articles WHERE slug=? LIMIT 1), ?, ?);")
+        .bind(author)
+        .bind(comment_body)
+        .bind(filter.article_slug.unwrap())
+        .bind(parent_id)
+        .bind(depth)
         .execute(conn)    
         .await?;
 
     let order_by = crate::filters::OrderByFilter::Descending("id");
     let limit_filter = crate::filters::LimitOffsetFilter { limit: Some(1), offset: None };
 
-    let comments_response = get_comments(conn, filter, order_by, limit_filter).await?;
+    let comments_response = get_comments(conn, filter, order_by, limit_filter, Some(author)).await?;
     if let Some(comment) = comments_response.into_iter().next() {
         Ok(comment)
     } else {
@@ -234,46 +660,145 @@ pub(crate) async fn add_comment(conn: &Pool<Sqlite>,
     }
 }
 
+pub(crate) async fn update_comment(conn: &Pool<Sqlite>,
+    filter: crate::filters::CommentFilterByValues<'_>,
+    comment_body: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    // tombstoned comments are not editable
+    let statement = format!("\
+        UPDATE comments SET body=?, edited=1, updatedAt=datetime('now') \
+            WHERE {} AND deleted=0", filter);
+
+    sqlx::query(&statement)
+        .bind(comment_body)
+        .execute(conn)    
+        .await
+}
+
 pub(crate) async fn delete_comments(conn: &Pool<Sqlite>,
     filter: crate::filters::CommentFilterByValues<'_>
 ) -> Result<SqliteQueryResult, sqlx::Error> {
-
-    let statement = format!("DELETE FROM comments WHERE {}", filter);
+    // comments having replies are tombstoned to keep their threads
+    let statement = format!("\
+        UPDATE comments SET deletedAt=COALESCE(deletedAt, datetime('now')), \
+            deleted=(id IN (SELECT parentId FROM comments AS replies \
+                WHERE parentId IS NOT NULL AND (replies.deletedAt IS NULL OR replies.deleted=1))) \
+            WHERE {}", 
+        filter);
     
     sqlx::query(&statement)
         .execute(conn)    
         .await
 }
 
-pub(crate) async fn get_tags(conn: &Pool<Sqlite>,
-) -> Result<article::TagList, errors::BackendError>  {
-
-    let statement = format!("SELECT tagList FROM articles");
+// brings back comment deleted by the author no longer than window_minutes ago,
+// provided its article and parent comment are still there
+pub(crate) async fn restore_comment(conn: &Pool<Sqlite>,
+    id: i32,
+    article_slug: &str,
+    author: &str,
+    window_minutes: i64,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query(
+        "UPDATE comments SET deletedAt=NULL, deleted=0 
+        WHERE id=? AND author=? AND deletedAt > datetime('now', ?) 
+            AND article_id IN (SELECT id FROM articles WHERE slug=? AND deletedAt IS NULL) 
+            AND (parentId IS NULL OR parentId IN 
+                (SELECT id FROM comments AS parents WHERE parents.deletedAt IS NULL OR parents.deleted=1));
+        ")
+        .bind(id)
+        .bind(author)
+        .bind(format!("-{} minutes", window_minutes))
+        .bind(article_slug)
+        .execute(conn)    
+        .await
+}
 
-    let all_tags: Vec<String> = sqlx::query_scalar(&statement)
-        .fetch_all(conn)  
+// removes articles and comments deleted more than window_minutes ago,
+// tombstones still having replies only lose their text
+pub(crate) async fn purge_deleted(conn: &Pool<Sqlite>,
+    window_minutes: i64,
+) -> Result<u64, errors::BackendError> {
+    let cutoff = format!("-{} minutes", window_minutes);
+    let mut tx = conn.begin().await?;
+
+    let articles = sqlx::query("DELETE FROM articles WHERE deletedAt <= datetime('now', ?);")
+        .bind(&cutoff)
+        .execute(&mut tx)
+        .await?;
+    let comments = sqlx::query("DELETE FROM comments WHERE deletedAt <= datetime('now', ?) AND deleted=0;")
+        .bind(&cutoff)
+        .execute(&mut tx)
+        .await?;
+    sqlx::query("UPDATE comments SET body='' WHERE deletedAt <= datetime('now', ?) AND deleted=1;")
+        .bind(&cutoff)
+        .execute(&mut tx)
         .await?;
 
-    let mut all_tags_hash_set 
-        = std::collections::HashSet::<&str>::with_capacity(200*all_tags.len());
-
-    for tags_delimited in &all_tags {
-        let tag_hash_set = 
-            tags_delimited.split(",")
-                .map(|tag| tag.trim())
-                .filter(|tag| *tag != "")
-                .collect::<std::collections::HashSet<&str>>();
-
-        all_tags_hash_set.extend(tag_hash_set);    
+    // tombstone is needed only while its thread has replies, purging one 
+    // may leave its parent tombstone without replies as well
+    let mut tombstones = 0;
+    loop {
+        let purged = sqlx::query("DELETE FROM comments WHERE deletedAt <= datetime('now', ?) AND deleted=1 
+            AND NOT EXISTS (SELECT 1 FROM comments AS replies WHERE replies.parentId = comments.id);")
+            .bind(&cutoff)
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+        if 0 == purged {
+            break;
+        }
+        tombstones += purged;
     }
+    tx.commit().await?;
 
-    let mut tags: Vec<String> = all_tags_hash_set
-        .into_iter()
-        .map(|tag| tag.to_string() )
-        .collect();
+    Ok(articles.rows_affected() + comments.rows_affected() + tombstones)
+}
+
+pub(crate) async fn follow_tag(conn: &Pool<Sqlite>,
+    username: &str,
+    tag: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query("INSERT INTO followed_tags (username, tag) VALUES(?, ?) ON CONFLICT DO NOTHING;")
+        .bind(username)
+        .bind(tag)
+        .execute(conn)
+        .await
+}
 
-    tags.sort_by_key(|tag| tag.to_lowercase());
+pub(crate) async fn unfollow_tag(conn: &Pool<Sqlite>,
+    username: &str,
+    tag: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query("DELETE FROM followed_tags WHERE username=? AND tag=?;")
+        .bind(username)
+        .bind(tag)
+        .execute(conn)
+        .await
+}
 
+pub(crate) async fn get_followed_tags(conn: &Pool<Sqlite>,
+    username: &str,
+) -> Result<article::TagList, errors::BackendError>  {
+    let tags = sqlx::query_scalar("SELECT tag FROM followed_tags WHERE username=? ORDER BY tag ASC;")
+        .bind(username)
+        .fetch_all(conn)
+        .await?;
     Ok(article::TagList {tags})
 }
 
+pub(crate) async fn get_tags(conn: &Pool<Sqlite>,
+) -> Result<article::TagList, errors::BackendError>  {
+    let filter = filters::TagCloudFilter::default();
+
+    let statement = format!("\
+        SELECT DISTINCT article_tags.tag FROM article_tags \
+            INNER JOIN articles ON articles.id = article_tags.article_id \
+            WHERE {} ORDER BY article_tags.tag ASC", filter);
+
+    let tags = sqlx::query_scalar(&statement)
+        .fetch_all(conn)  
+        .await?;
+
+    Ok(article::TagList {tags})
+}
@@ -0,0 +1,69 @@
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite};
+use crate::{models::event, errors};
+
+pub(crate) async fn store_event(conn: &Pool<Sqlite>,
+    kind: event::EventKind,
+    recipient: Option<&str>,
+    author: Option<&str>,
+    article_slug: Option<&str>,
+    payload: String,
+) -> Result<event::Event, errors::BackendError>  {
+
+    let query_res = sqlx::query(
+        "INSERT INTO events (kind, recipient, author, articleSlug, payload, createdAt) 
+        VALUES( ?, ?, ?, ?, ?, datetime('now'));
+        ")
+        .bind(kind)
+        .bind(recipient)
+        .bind(author)
+        .bind(article_slug)
+        .bind(&payload)
+        .execute(conn)
+        .await?;
+
+    Ok(event::Event {
+        id: query_res.last_insert_rowid(),
+        kind,
+        recipient: recipient.map(|recipient| recipient.to_string()),
+        author: author.map(|author| author.to_string()),
+        article_slug: article_slug.map(|slug| slug.to_string()),
+        payload,
+    })
+}
+
+// events are kept only for a short time, enough for reconnecting clients
+pub(crate) async fn prune_events(conn: &Pool<Sqlite>,
+    keep_minutes: i64,
+) -> Result<(), errors::BackendError>  {
+
+    sqlx::query("DELETE FROM events WHERE createdAt < datetime('now', ?);")
+        .bind(format!("-{} minutes", keep_minutes))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn events_since(conn: &Pool<Sqlite>,
+    last_event_id: i64,
+    recipient: &str,
+    article_slug: Option<&str>,
+) -> Result<Vec<event::Event>, errors::BackendError>  {
+
+    let events = sqlx::query_as::<_, event::Event>(
+        "SELECT * FROM events WHERE id > ? AND 
+            (recipient = ? 
+                OR (kind = ? AND articleSlug = ?) 
+                OR (kind = ? AND author IN (SELECT celeb_name FROM followers WHERE follower_name = ?))) 
+        ORDER BY id ASC;
+        ")
+        .bind(last_event_id)
+        .bind(recipient)
+        .bind(event::EventKind::Comment)
+        .bind(article_slug)
+        .bind(event::EventKind::Article)
+        .bind(recipient)
+        .fetch_all(conn)
+        .await?;
+    Ok(events)
+}
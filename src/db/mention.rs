@@ -0,0 +1,64 @@
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite};
+use crate::errors;
+
+// users mentioned in article body
+pub(crate) async fn get_mentions(conn: &Pool<Sqlite>,
+    article_id: i32,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT username FROM mentions WHERE article_id=? AND comment_id IS NULL ORDER BY rowid;")
+        .bind(article_id)
+        .fetch_all(conn)
+        .await
+}
+
+pub(crate) async fn get_comment_mentions(conn: &Pool<Sqlite>,
+    comment_id: i32,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT username FROM mentions WHERE comment_id=? ORDER BY rowid;")
+        .bind(comment_id)
+        .fetch_all(conn)
+        .await
+}
+
+// replaces mentions in article body, or in its comment if comment_id is given,
+// with usernames of existing users among the given ones,
+// returns users not mentioned in the text before
+pub(crate) async fn set_mentions(conn: &Pool<Sqlite>,
+    article_id: i32,
+    comment_id: Option<i32>,
+    usernames: &[String],
+) -> Result<Vec<String>, errors::BackendError> {
+    let mut tx = super::begin_immediate(conn).await?;
+
+    let previous: Vec<String> = 
+        sqlx::query_scalar("SELECT username FROM mentions WHERE article_id=? AND comment_id IS ?;")
+        .bind(article_id)
+        .bind(comment_id)
+        .fetch_all(&mut tx)
+        .await?;
+    sqlx::query("DELETE FROM mentions WHERE article_id=? AND comment_id IS ?;")
+        .bind(article_id)
+        .bind(comment_id)
+        .execute(&mut tx)
+        .await?;
+
+    let mut added = Vec::new();
+    for username in usernames {
+        let query_res = sqlx::query(
+            "INSERT INTO mentions (username, article_id, comment_id) 
+            SELECT username, ?, ? FROM users WHERE username=?;
+            ")
+            .bind(article_id)
+            .bind(comment_id)
+            .bind(username)
+            .execute(&mut tx)
+            .await?;
+        if 0 < query_res.rows_affected() && !previous.contains(username) {
+            added.push(username.clone());
+        }
+    }
+    tx.commit().await?;
+
+    Ok(added)
+}
@@ -1,9 +1,18 @@
-use sqlx::{Pool};
+use sqlx::{Pool, Transaction};
 use sqlx::sqlite::{Sqlite};
 use crate::config;
 
 pub(crate) mod article;
 pub(crate) mod user;
+pub(crate) mod reaction;
+pub(crate) mod notification;
+pub(crate) mod event;
+pub(crate) mod webhook;
+pub(crate) mod report;
+pub(crate) mod tag;
+pub(crate) mod stats;
+pub(crate) mod upload;
+pub(crate) mod mention;
 pub(crate) mod schema;
 
 pub(crate) async fn connect(config: &config::Config) -> Result<Pool<Sqlite>, sqlx::Error> {
@@ -13,3 +22,14 @@ pub(crate) async fn connect(config: &config::Config) -> Result<Pool<Sqlite>, sql
 }
 
 
+
+// transaction holding the write lock from its start, as BEGIN IMMEDIATE would;
+// deferred one that reads before writing fails with SQLITE_BUSY instead of waiting
+// when another connection writes meanwhile, and a write changing nothing takes the lock
+pub(crate) async fn begin_immediate(conn: &Pool<Sqlite>) -> Result<Transaction<'static, Sqlite>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    sqlx::query("UPDATE users SET username=username WHERE 0;")
+        .execute(&mut tx)
+        .await?;
+    Ok(tx)
+}
@@ -0,0 +1,114 @@
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
+use crate::{models::notification, filters, errors};
+
+// returns id of created notification, None if it has been dropped
+pub(crate) async fn notify(conn: &Pool<Sqlite>,
+    notification: &notification::NewNotification<'_>,
+) -> Result<Option<i32>, errors::BackendError>  {
+    // nobody is notified about own actions
+    if notification.recipient == notification.actor {
+        return Ok(None);
+    }
+
+    let query_res = sqlx::query(
+        "INSERT INTO notifications (recipient, actor, kind, articleId, commentId, read, createdAt)
+        SELECT ?, ?, ?, (SELECT id FROM articles WHERE slug=?), ?, 0, datetime('now')
+        WHERE NOT (? AND EXISTS 
+            (SELECT 1 FROM muted_notifications WHERE username=? AND kind=?));
+        ")
+        .bind(notification.recipient)
+        .bind(notification.actor)
+        .bind(notification.kind)
+        .bind(notification.article_slug)
+        .bind(notification.comment_id)
+        .bind(notification.kind.can_be_muted())
+        .bind(notification.recipient)
+        .bind(notification.kind)
+        .execute(conn)
+        .await?;
+
+    Ok((0 < query_res.rows_affected()).then(|| query_res.last_insert_rowid() as i32))
+}
+
+pub(crate) async fn get_notifications(conn: &Pool<Sqlite>,
+    filter: &filters::NotificationFilter,
+    limit_offset: filters::LimitOffsetFilter,
+) -> Result<Vec<notification::NotificationResponse>, errors::BackendError>  {
+
+    let statement = format!("\
+        SELECT notifications.*, articles.slug AS articleSlug FROM notifications \
+            LEFT JOIN articles ON articles.id = notifications.articleId \
+            WHERE {} ORDER BY notifications.id DESC {}", 
+        filter, limit_offset);
+
+    let notifications = sqlx::query_as::<_, notification::Notification>(&statement)
+        .fetch_all(conn)
+        .await?;
+
+    let mut multiple_notifications = Vec::<notification::NotificationResponse>::with_capacity(notifications.len());
+
+    for notification in notifications {
+        let actor = super::user::get_profile(conn, &notification.actor).await;
+        multiple_notifications.push( notification::NotificationResponse { actor, notification } );
+    }
+    Ok(multiple_notifications)
+}
+
+pub(crate) async fn count_notifications(conn: &Pool<Sqlite>,
+    filter: &filters::NotificationFilter,
+) -> Result<i64, errors::BackendError>  {
+
+    let statement = format!("SELECT COUNT(*) FROM notifications WHERE {}", filter);
+
+    let count = sqlx::query_scalar(&statement)
+        .fetch_one(conn)
+        .await?;
+    Ok(count)
+}
+
+pub(crate) async fn mark_read(conn: &Pool<Sqlite>,
+    filter: &filters::NotificationFilter,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let statement = format!("UPDATE notifications SET read=1 WHERE {}", filter);
+
+    sqlx::query(&statement)
+        .execute(conn)
+        .await
+}
+
+pub(crate) async fn get_preferences(conn: &Pool<Sqlite>,
+    username: &str,
+) -> Result<notification::NotificationPreferences, errors::BackendError>  {
+
+    let muted = sqlx::query_scalar("SELECT kind FROM muted_notifications WHERE username=?;")
+        .bind(username)
+        .fetch_all(conn)
+        .await?;
+
+    Ok(notification::NotificationPreferences { muted })
+}
+
+pub(crate) async fn set_preferences(conn: &Pool<Sqlite>,
+    username: &str,
+    preferences: &notification::NotificationPreferences,
+) -> Result<notification::NotificationPreferences, errors::BackendError>  {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("DELETE FROM muted_notifications WHERE username=?;")
+        .bind(username)
+        .execute(&mut tx)
+        .await?;
+
+    for kind in &preferences.muted {
+        sqlx::query("INSERT INTO muted_notifications (username, kind) VALUES(?, ?) ON CONFLICT DO NOTHING;")
+            .bind(username)
+            .bind(kind)
+            .execute(&mut tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    get_preferences(conn, username).await
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
+use crate::{models::reaction, filters};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ReactionTarget {
+    Article,
+    Comment,
+}
+
+impl ReactionTarget {
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Article => "article_reactions",
+            Self::Comment => "comment_reactions",
+        }
+    }
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Article => "article_id",
+            Self::Comment => "comment_id",
+        }
+    }
+    fn source(&self) -> &'static str {
+        match self {
+            Self::Article => "articles",
+            // deleted and tombstoned comments cannot be reacted to
+            Self::Comment => "(SELECT * FROM comments WHERE deletedAt IS NULL)",
+        }
+    }
+}
+
+pub(crate) async fn add_reaction<F: filters::Filter>(conn: &Pool<Sqlite>,
+    target: ReactionTarget,
+    filter: F,
+    username: &str,
+    kind: reaction::ReactionKind,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let statement = format!("\
+        INSERT INTO {} ({}, username, kind) \
+            SELECT id, ?, ? FROM {} WHERE {} \
+            ON CONFLICT DO NOTHING;",
+        target.table(), target.column(), target.source(), filter);
+
+    sqlx::query(&statement)
+        .bind(username)
+        .bind(kind.as_str())
+        .execute(conn)
+        .await
+}
+
+pub(crate) async fn remove_reaction<F: filters::Filter>(conn: &Pool<Sqlite>,
+    target: ReactionTarget,
+    filter: F,
+    username: &str,
+    kind: reaction::ReactionKind,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let statement = format!("\
+        DELETE FROM {} WHERE {} IN (SELECT id FROM {} WHERE {}) \
+            AND username=? AND kind=?;",
+        target.table(), target.column(), target.source(), filter);
+
+    sqlx::query(&statement)
+        .bind(username)
+        .bind(kind.as_str())
+        .execute(conn)
+        .await
+}
+
+// counts of every reaction kind, flagged if reacted by viewer, 
+// for each of the given articles or comments
+pub(crate) async fn get_reactions(conn: &Pool<Sqlite>,
+    target: ReactionTarget,
+    ids: &[i32],
+    viewer: Option<&str>,
+) -> Result<HashMap<i32, Vec<reaction::ReactionCount>>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let id_list = ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    let statement = format!("\
+        SELECT {}, kind, COUNT(*), IFNULL(SUM(username=?), 0) FROM {} \
+            WHERE {} IN ({}) GROUP BY {}, kind;",
+        target.column(), target.table(), target.column(), id_list, target.column());
+
+    let counts = sqlx::query_as::<_, (i32, String, i64, i64)>(&statement)
+        .bind(viewer)
+        .fetch_all(conn)
+        .await?;
+
+    Ok(ids.iter()
+        .map(|id| {
+            let reactions = reaction::ReactionKind::ALL.iter()
+                .map(|kind| {
+                    let (count, reacted) = counts.iter()
+                        .find(|(counted_id, counted_kind, _, _)| counted_id == id && counted_kind == kind.as_str())
+                        .map(|(_, _, count, reacted)| (*count, 0 < *reacted))
+                        .unwrap_or((0, false));
+
+                    reaction::ReactionCount {
+                        kind: *kind,
+                        emoji: kind.emoji(),
+                        count,
+                        reacted,
+                    }
+                })
+                .collect();
+            (*id, reactions)
+        })
+        .collect())
+}
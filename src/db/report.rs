@@ -0,0 +1,117 @@
+use sqlx::{Pool, Transaction};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
+use crate::{models::report, filters, errors};
+
+pub(crate) async fn create_report(conn: &Pool<Sqlite>,
+    reporter: &str,
+    article_slug: &str,
+    comment_id: Option<i32>,
+    reason: &str,
+) -> Result<report::Report, errors::BackendError>  {
+
+    let query_res = sqlx::query(
+        "INSERT INTO reports (reporter, articleId, commentId, reason, status, createdAt) 
+        SELECT ?, id, ?, ?, ?, datetime('now') FROM articles WHERE slug=?;
+        ")
+        .bind(reporter)
+        .bind(comment_id)
+        .bind(reason)
+        .bind(report::ReportStatus::Open)
+        .bind(article_slug)
+        .execute(conn)
+        .await?;
+    // nothing is inserted when article is gone meanwhile
+    if query_res.rows_affected() != 1 {
+        return Err(errors::BackendError::NoArticleFound);
+    }
+    let id = query_res.last_insert_rowid() as i32;
+
+    let filter = filters::ReportFilter::default().id(id);
+    get_reports(conn, &filter, filters::LimitOffsetFilter::default())
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(errors::BackendError::NoReportFound(id))
+}
+
+pub(crate) async fn get_reports(conn: &Pool<Sqlite>,
+    filter: &filters::ReportFilter,
+    limit_offset: filters::LimitOffsetFilter,
+) -> Result<Vec<report::Report>, errors::BackendError>  {
+    // queue is served oldest first
+    let statement = format!("\
+        SELECT reports.*, articles.slug AS articleSlug, \
+            IFNULL(comments.author, articles.author) AS author FROM reports \
+            INNER JOIN articles ON articles.id = reports.articleId \
+            LEFT JOIN comments ON comments.id = reports.commentId \
+            WHERE {} ORDER BY reports.id ASC {}", 
+        filter, limit_offset);
+
+    let reports = sqlx::query_as::<_, report::Report>(&statement)
+        .fetch_all(conn)
+        .await?;
+    Ok(reports)
+}
+
+pub(crate) async fn count_reports(conn: &Pool<Sqlite>,
+    filter: &filters::ReportFilter,
+) -> Result<i64, errors::BackendError>  {
+
+    let statement = format!("SELECT COUNT(*) FROM reports WHERE {}", filter);
+
+    let count = sqlx::query_scalar(&statement)
+        .fetch_one(conn)
+        .await?;
+    Ok(count)
+}
+
+// resolves open report and applies the moderation action in one transaction,
+// nothing is applied when the report has been resolved meanwhile
+pub(crate) async fn resolve_report(conn: &Pool<Sqlite>,
+    report: &report::Report,
+    status: report::ReportStatus,
+    action: Option<report::ModerationAction>,
+    moderator: &str,
+) -> Result<u64, errors::BackendError> {
+    let mut tx = conn.begin().await?;
+
+    let resolved = sqlx::query(
+        "UPDATE reports SET status=?, action=?, moderator=?, resolvedAt=datetime('now') 
+        WHERE id=? AND status=?;
+        ")
+        .bind(status)
+        .bind(action)
+        .bind(moderator)
+        .bind(report.id)
+        .bind(report::ReportStatus::Open)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+    if 0 == resolved {
+        return Ok(0);
+    }
+
+    match action {
+        Some(report::ModerationAction::Hide) => { hide_reported(&mut tx, report).await?; },
+        Some(report::ModerationAction::Suspend) => { super::user::suspend(&mut tx, &report.author).await?; },
+        Some(report::ModerationAction::Warn) | None => (),
+    }
+    tx.commit().await?;
+    Ok(resolved)
+}
+
+async fn hide_reported(tx: &mut Transaction<'_, Sqlite>,
+    report: &report::Report,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    match report.comment_id {
+        Some(comment_id) => sqlx::query("UPDATE comments SET hidden=1 WHERE id=?;")
+            .bind(comment_id)
+            .execute(&mut *tx)
+            .await,
+        None => sqlx::query("UPDATE articles SET hidden=1 WHERE slug=?;")
+            .bind(&report.article_slug)
+            .execute(&mut *tx)
+            .await,
+    }
+}
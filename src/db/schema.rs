@@ -44,7 +44,9 @@ impl Schema {
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 username TEXT UNIQUE NOT NULL,
                 email TEXT UNIQUE NOT NULL,
-                hashed_password TEXT NOT NULL
+                hashed_password TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                suspended BOOLEAN NOT NULL DEFAULT 0
             );
         ")
         .execute(&sqlite_pool)    
@@ -82,6 +84,74 @@ impl Schema {
         ")
         .execute(&sqlite_pool)    
         .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS tags (
+                name TEXT PRIMARY KEY COLLATE NOCASE,
+                description TEXT
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS tag_aliases (
+                alias TEXT PRIMARY KEY COLLATE NOCASE,
+                tag TEXT NOT NULL COLLATE NOCASE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS followed_tags (
+                username TEXT NOT NULL,
+                tag TEXT NOT NULL COLLATE NOCASE,
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Pair UNIQUE (username, tag)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS blocks (
+                blocker_name TEXT NOT NULL,
+                blocked_name TEXT NOT NULL,
+                FOREIGN KEY (blocker_name)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                FOREIGN KEY (blocked_name)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Pair UNIQUE (blocker_name,blocked_name)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS mutes (
+                muter_name TEXT NOT NULL,
+                muted_name TEXT NOT NULL,
+                FOREIGN KEY (muter_name)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                FOREIGN KEY (muted_name)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Pair UNIQUE (muter_name,muted_name)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
     
         sqlx::query("
             CREATE TABLE IF NOT EXISTS articles (
@@ -94,6 +164,13 @@ impl Schema {
                 createdAt TEXT NOT NULL,
                 updatedAt TEXT NOT NULL,
                 author TEXT NOT NULL,   
+                version INTEGER NOT NULL DEFAULT 1,
+                hidden BOOLEAN NOT NULL DEFAULT 0,
+                deletedAt TEXT,
+                wordCount INTEGER NOT NULL DEFAULT 0,
+                readingTimeMinutes INTEGER NOT NULL DEFAULT 0,
+                generatedDescription BOOLEAN NOT NULL DEFAULT 0,
+                viewsCount INTEGER NOT NULL DEFAULT 0,
             FOREIGN KEY (author)
                 REFERENCES users (username) 
                 ON UPDATE CASCADE
@@ -106,6 +183,7 @@ impl Schema {
             CREATE TABLE IF NOT EXISTS favorite_articles (
                 id INTEGER NOT NULL,
                 username TEXT NOT NULL,
+                favoritedAt TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (id)
                     REFERENCES articles (id) 
                     ON DELETE CASCADE
@@ -119,6 +197,24 @@ impl Schema {
         .execute(&sqlite_pool)    
         .await?;
     
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS bookmarks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                article_id INTEGER NOT NULL,
+                username TEXT NOT NULL,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Pair UNIQUE (article_id, username)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+    
         sqlx::query("
             CREATE TABLE IF NOT EXISTS comments (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -127,9 +223,18 @@ impl Schema {
                 createdAt TEXT NOT NULL,
                 updatedAt TEXT NOT NULL,
                 author TEXT NOT NULL,   
+                parentId INTEGER,
+                depth INTEGER NOT NULL DEFAULT 0,
+                deleted BOOLEAN NOT NULL DEFAULT 0,
+                edited BOOLEAN NOT NULL DEFAULT 0,
+                hidden BOOLEAN NOT NULL DEFAULT 0,
+                deletedAt TEXT,
             FOREIGN KEY (article_id)
                 REFERENCES articles (id) 
                 ON DELETE CASCADE
+            FOREIGN KEY (parentId)
+                REFERENCES comments (id) 
+                ON DELETE CASCADE
             FOREIGN KEY (author)
                 REFERENCES users (username) 
                 ON DELETE CASCADE
@@ -139,7 +244,420 @@ impl Schema {
         .execute(&sqlite_pool)    
         .await?;
 
+        // columns added after the tables were first created, databases
+        // created earlier get them on startup
+        for (table, column, definition) in [
+            ("users", "version", "INTEGER NOT NULL DEFAULT 1"),
+            ("articles", "version", "INTEGER NOT NULL DEFAULT 1"),
+            ("comments", "parentId", "INTEGER REFERENCES comments (id) ON DELETE CASCADE"),
+            ("comments", "depth", "INTEGER NOT NULL DEFAULT 0"),
+            ("comments", "deleted", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("comments", "edited", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("users", "suspended", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("articles", "hidden", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("comments", "hidden", "BOOLEAN NOT NULL DEFAULT 0"),
+            ("articles", "deletedAt", "TEXT"),
+            ("comments", "deletedAt", "TEXT"),
+            // non-constant default is not allowed when adding a column,
+            // favorites of unknown time are considered old
+            ("favorite_articles", "favoritedAt", "TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'"),
+            ("articles", "wordCount", "INTEGER NOT NULL DEFAULT 0"),
+            ("articles", "readingTimeMinutes", "INTEGER NOT NULL DEFAULT 0"),
+            ("articles", "generatedDescription", "BOOLEAN NOT NULL DEFAULT 0"),
+        ] {
+            add_column(&sqlite_pool, table, column, definition).await?;
+        }
+
+        // databases created before slugs were unique may hold duplicates,
+        // later articles get their id appended until the index can be built
+        let slug_index: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type='index' AND name='articles_slug';")
+            .fetch_optional(&sqlite_pool)
+            .await?;
+        if slug_index.is_none() {
+            while 0 < sqlx::query("
+                UPDATE articles SET slug = slug || '-' || id 
+                WHERE EXISTS (SELECT 1 FROM articles AS earlier WHERE earlier.slug = articles.slug AND earlier.id < articles.id);
+            ")
+            .execute(&sqlite_pool)    
+            .await?
+            .rows_affected() {}
+        }
+
+        sqlx::query("
+            CREATE UNIQUE INDEX IF NOT EXISTS articles_slug ON articles (slug);
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_views (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                article_id INTEGER NOT NULL,
+                viewer TEXT NOT NULL,
+                viewedAt TEXT NOT NULL,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS article_views_article ON article_views (article_id, viewer, viewedAt);
+            CREATE INDEX IF NOT EXISTS article_views_viewed ON article_views (viewedAt);
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // views per article and day, kept after single views are pruned
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_view_days (
+                article_id INTEGER NOT NULL,
+                day TEXT NOT NULL,
+                views INTEGER NOT NULL,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+                PRIMARY KEY (article_id, day)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+        // views recorded before totals were kept are counted once
+        if add_column(&sqlite_pool, "articles", "viewsCount", "INTEGER NOT NULL DEFAULT 0").await? {
+            sqlx::query("
+                UPDATE articles SET viewsCount = (SELECT COUNT(*) FROM article_views WHERE article_id = articles.id);
+                INSERT OR IGNORE INTO article_view_days (article_id, day, views) 
+                    SELECT article_id, date(viewedAt), COUNT(*) FROM article_views GROUP BY article_id, date(viewedAt);
+            ")
+            .execute(&sqlite_pool)    
+            .await?;
+        }
+
+        // trending scores, refreshed periodically from recent activity
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_scores (
+                article_id INTEGER PRIMARY KEY,
+                trendingScore REAL NOT NULL,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // the same file uploaded by several users is stored once,
+        // but counts towards quota of each of them
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS uploads (
+                id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                contentType TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                createdAt TEXT NOT NULL,
+                PRIMARY KEY (id, username),
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // processed avatars of every size, stored as uploads
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS avatars (
+                username TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                upload_id TEXT NOT NULL,
+                PRIMARY KEY (username, size),
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // users mentioned in article body, or in its comment
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS mentions (
+                username TEXT NOT NULL,
+                article_id INTEGER NOT NULL,
+                comment_id INTEGER,
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE,
+                FOREIGN KEY (comment_id)
+                    REFERENCES comments (id) 
+                    ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS mentions_text ON mentions (article_id, comment_id);
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // rendered bodies cached per article version
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_html (
+                article_id INTEGER NOT NULL,
+                version INTEGER NOT NULL,
+                bodyHtml TEXT NOT NULL,
+                PRIMARY KEY (article_id, version),
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // tags of articles one per row, kept along with tagList for counting 
+        // and prefix lookups of tags
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_tags (
+                article_id INTEGER NOT NULL,
+                tag TEXT NOT NULL COLLATE NOCASE,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+                CONSTRAINT Pair UNIQUE (article_id, tag)
+            );
+            CREATE INDEX IF NOT EXISTS article_tags_tag ON article_tags (tag);
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        // fills article_tags for articles written before it existed,
+        // once filled it is kept up to date along with tagList
+        let article_tags_empty: bool = sqlx::query_scalar("SELECT NOT EXISTS (SELECT 1 FROM article_tags);")
+            .fetch_one(&sqlite_pool)
+            .await?;
+        if article_tags_empty {
+            sqlx::query("
+                WITH RECURSIVE split(article_id, tag, rest) AS (
+                    SELECT id, '', tagList || ',' FROM articles WHERE tagList IS NOT NULL
+                    UNION ALL
+                    SELECT article_id, 
+                        lower(trim(substr(rest, 1, instr(rest, ',') - 1))), 
+                        substr(rest, instr(rest, ',') + 1) 
+                    FROM split WHERE rest <> ''
+                )
+                INSERT OR IGNORE INTO article_tags (article_id, tag) 
+                    SELECT article_id, tag FROM split WHERE tag <> '';
+            ")
+            .execute(&sqlite_pool)    
+            .await?;
+        }
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS slug_history (
+                slug TEXT PRIMARY KEY,
+                article_id INTEGER NOT NULL,
+            FOREIGN KEY (article_id)
+                REFERENCES articles (id) 
+                ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS article_reactions (
+                article_id INTEGER NOT NULL,
+                username TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                FOREIGN KEY (article_id)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Reaction UNIQUE (article_id, username, kind)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS comment_reactions (
+                comment_id INTEGER NOT NULL,
+                username TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                FOREIGN KEY (comment_id)
+                    REFERENCES comments (id) 
+                    ON DELETE CASCADE
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Reaction UNIQUE (comment_id, username, kind)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recipient TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                articleId INTEGER,
+                commentId INTEGER,
+                read BOOLEAN NOT NULL DEFAULT 0,
+                createdAt TEXT NOT NULL,
+            FOREIGN KEY (recipient)
+                REFERENCES users (username) 
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            FOREIGN KEY (actor)
+                REFERENCES users (username) 
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            FOREIGN KEY (articleId)
+                REFERENCES articles (id) 
+                ON DELETE CASCADE
+            FOREIGN KEY (commentId)
+                REFERENCES comments (id) 
+                ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS muted_notifications (
+                username TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                FOREIGN KEY (username)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+                CONSTRAINT Muted UNIQUE (username, kind)
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                recipient TEXT,
+                author TEXT,
+                articleSlug TEXT,
+                payload TEXT NOT NULL,
+                createdAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS events_created ON events (createdAt);
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+        add_column(&sqlite_pool, "events", "author", "TEXT").await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                owner TEXT NOT NULL,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                createdAt TEXT NOT NULL,
+                FOREIGN KEY (owner)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS webhook_events (
+                webhook_id INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                UNIQUE (webhook_id, event),
+                FOREIGN KEY (webhook_id)
+                    REFERENCES webhooks (id) 
+                    ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                webhookId INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                responseStatus INTEGER,
+                lastError TEXT,
+                createdAt TEXT NOT NULL,
+                nextAttemptAt TEXT,
+                deliveredAt TEXT,
+                FOREIGN KEY (webhookId)
+                    REFERENCES webhooks (id) 
+                    ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
+        sqlx::query("
+            CREATE TABLE IF NOT EXISTS reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                reporter TEXT NOT NULL,
+                articleId INTEGER NOT NULL,
+                commentId INTEGER,
+                reason TEXT NOT NULL,
+                status TEXT NOT NULL,
+                action TEXT,
+                moderator TEXT,
+                createdAt TEXT NOT NULL,
+                resolvedAt TEXT,
+                FOREIGN KEY (reporter)
+                    REFERENCES users (username) 
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                FOREIGN KEY (articleId)
+                    REFERENCES articles (id) 
+                    ON DELETE CASCADE,
+                FOREIGN KEY (commentId)
+                    REFERENCES comments (id) 
+                    ON DELETE CASCADE
+            );
+        ")
+        .execute(&sqlite_pool)    
+        .await?;
+
         Ok(sqlite_pool)
     }
 }
 
+async fn add_column(sqlite_pool: &SqlitePool, 
+    table: &str, 
+    column: &str, 
+    definition: &str,
+) -> Result<bool, sqlx::Error> {
+    let exists: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info(?) WHERE name=?;")
+        .bind(table)
+        .bind(column)
+        .fetch_one(sqlite_pool)
+        .await?;
+
+    if !exists {
+        let statement = format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition);
+        sqlx::query(&statement)
+            .execute(sqlite_pool)
+            .await?;
+    }
+    Ok(!exists)
+}
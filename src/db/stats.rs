@@ -0,0 +1,65 @@
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite};
+use crate::{models::stats, filters, errors};
+
+// per article totals and bucketed activity of articles by the author
+pub(crate) async fn get_author_stats(conn: &Pool<Sqlite>,
+    author: &str,
+    filter: filters::StatsFilter,
+) -> Result<stats::AuthorStats, errors::BackendError>  {
+    let totals = sqlx::query_as::<_, (i32, String, String, i64, i64, i64)>(
+        "SELECT id, slug, title, 
+            viewsCount,
+            (SELECT COUNT(*) FROM favorite_articles WHERE favorite_articles.id = articles.id),
+            (SELECT COUNT(*) FROM comments WHERE article_id = articles.id AND deletedAt IS NULL)
+        FROM articles WHERE author=? AND deletedAt IS NULL ORDER BY createdAt DESC;
+        ")
+        .bind(author)
+        .fetch_all(conn)
+        .await?;
+
+    let statement = format!("\
+        SELECT article_id, {} AS start, \
+            SUM(views), SUM(favorites), SUM(comments) \
+        FROM ( \
+            SELECT article_id, day AS at, views, 0 AS favorites, 0 AS comments FROM article_view_days \
+            UNION ALL \
+            SELECT id AS article_id, favoritedAt AS at, 0 AS views, 1 AS favorites, 0 AS comments FROM favorite_articles \
+            UNION ALL \
+            SELECT article_id, createdAt AS at, 0 AS views, 0 AS favorites, 1 AS comments FROM comments WHERE deletedAt IS NULL \
+        ) \
+        WHERE at >= date('now', ?) \
+            AND article_id IN (SELECT id FROM articles WHERE author=? AND deletedAt IS NULL) \
+        GROUP BY article_id, start \
+        ORDER BY start ASC", 
+        filter.bucket_start("at"));
+
+    let buckets = sqlx::query_as::<_, (i32, String, i64, i64, i64)>(&statement)
+        .bind(format!("-{} days", filter.days))
+        .bind(author)
+        .fetch_all(conn)
+        .await?;
+
+    let articles = totals.into_iter()
+        .map(|(id, slug, title, views_count, favorites_count, comments_count)| 
+            stats::ArticleStats {
+                id,
+                slug,
+                title,
+                views_count,
+                favorites_count,
+                comments_count,
+                buckets: buckets.iter()
+                    .filter(|(article_id, ..)| *article_id == id)
+                    .map(|(_, start, views, favorites, comments)| stats::StatsBucket {
+                        start: start.clone(),
+                        views: *views,
+                        favorites: *favorites,
+                        comments: *comments,
+                    })
+                    .collect(),
+            })
+        .collect();
+
+    Ok(stats::AuthorStats { articles })
+}
@@ -0,0 +1,283 @@
+use sqlx::{Pool, Transaction};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
+use crate::{models::tag, filters, errors};
+
+// condition on tagList matching the bound tag exactly, tagList is stored as 
+// comma terminated list, leading comma makes every tag enclosed in commas
+const HAS_TAG: &str = "instr(lower(',' || tagList), ',' || ? || ',') > 0";
+
+// resolves tag to its canonical name, tag is expected to be normalized
+pub(crate) async fn canonical(conn: &Pool<Sqlite>,
+    tag: &str,
+) -> Result<String, errors::BackendError>  {
+    let canonical = sqlx::query_scalar("SELECT tag FROM tag_aliases WHERE alias=?;")
+        .bind(tag)
+        .fetch_optional(conn)
+        .await?;
+    Ok(canonical.unwrap_or_else(|| tag.to_string()))
+}
+
+// canonical name looked up within the transaction that writes it
+async fn canonical_in(tx: &mut Transaction<'_, Sqlite>,
+    tag: &str,
+) -> Result<String, sqlx::Error>  {
+    let canonical = sqlx::query_scalar("SELECT tag FROM tag_aliases WHERE alias=?;")
+        .bind(tag)
+        .fetch_optional(&mut *tx)
+        .await?;
+    Ok(canonical.unwrap_or_else(|| tag.to_string()))
+}
+
+// maps stored tagList to canonical tags again, within the transaction writing the article,
+// tags may have become aliases since they were resolved for the request
+pub(crate) async fn canonical_tag_list(tx: &mut Transaction<'_, Sqlite>,
+    tag_list: &str,
+) -> Result<String, sqlx::Error>  {
+    let mut tags = Vec::<String>::new();
+    for tag in tag_list.split(',').filter(|tag| !tag.is_empty()) {
+        let tag = canonical_in(tx, tag).await?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags.iter().fold("".to_string(), |s, tag| format!("{}{},", s, tag)))
+}
+
+// normalizes tags and maps aliases to canonical tags, dropping empty and repeated ones
+pub(crate) async fn canonical_list(conn: &Pool<Sqlite>,
+    tags: &[String],
+) -> Result<Vec<String>, errors::BackendError>  {
+    let mut canonical_tags = Vec::<String>::with_capacity(tags.len());
+
+    for tag in tags {
+        let tag = tag::normalize(tag);
+        if tag.is_empty() {
+            continue;
+        }
+        let tag = canonical(conn, &tag).await?;
+        if !canonical_tags.contains(&tag) {
+            canonical_tags.push(tag);
+        }
+    }
+    Ok(canonical_tags)
+}
+
+pub(crate) async fn get_tag(conn: &Pool<Sqlite>,
+    name: &str,
+) -> Result<Option<tag::Tag>, errors::BackendError>  {
+    let description: Option<Option<String>> = sqlx::query_scalar("SELECT description FROM tags WHERE name=?;")
+        .bind(name)
+        .fetch_optional(conn)
+        .await?;
+    let aliases: Vec<String> = sqlx::query_scalar("SELECT alias FROM tag_aliases WHERE tag=? ORDER BY alias ASC;")
+        .bind(name)
+        .fetch_all(conn)
+        .await?;
+    let statement = format!("SELECT COUNT(*) FROM articles WHERE deletedAt IS NULL AND {}", HAS_TAG);
+    let articles_count: i64 = sqlx::query_scalar(&statement)
+        .bind(name)
+        .fetch_one(conn)
+        .await?;
+
+    if description.is_none() && aliases.is_empty() && 0 == articles_count {
+        return Ok(None);
+    }
+    Ok(Some(tag::Tag {
+        name: name.to_string(),
+        description: description.flatten(),
+        aliases,
+        articles_count,
+    }))
+}
+
+pub(crate) async fn set_description(conn: &Pool<Sqlite>,
+    name: &str,
+    description: Option<&str>,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tags (name, description) VALUES(?, ?) 
+        ON CONFLICT(name) DO UPDATE SET description=excluded.description;
+        ")
+        .bind(name)
+        .bind(description)
+        .execute(conn)
+        .await
+}
+
+// makes `alias` an alias of `name`, both are resolved and alias is checked to be unused 
+// while holding the write lock, so that no article gets tagged by it meanwhile, 
+// returns canonical name of the tag
+pub(crate) async fn add_alias(conn: &Pool<Sqlite>,
+    alias: &str,
+    name: &str,
+) -> Result<String, errors::BackendError> {
+    let mut tx = super::begin_immediate(conn).await?;
+    let name = canonical_in(&mut tx, name).await?;
+    let alias = canonical_in(&mut tx, alias).await?;
+
+    if alias == name {
+        return Err(errors::BackendError::ValidationError(format!("tag {} cannot be alias of itself", name)));
+    }
+    let statement = format!("SELECT EXISTS (SELECT 1 FROM tags WHERE name=?) 
+        OR EXISTS (SELECT 1 FROM tag_aliases WHERE tag=?) 
+        OR EXISTS (SELECT 1 FROM articles WHERE deletedAt IS NULL AND {})", HAS_TAG);
+    let in_use: bool = sqlx::query_scalar(&statement)
+        .bind(&alias)
+        .bind(&alias)
+        .bind(&alias)
+        .fetch_one(&mut tx)
+        .await?;
+    if in_use {
+        return Err(errors::BackendError::ValidationError(format!("tag {} is in use, merge it instead", alias)));
+    }
+
+    sqlx::query(
+        "INSERT INTO tag_aliases (alias, tag) VALUES(?, ?) 
+        ON CONFLICT(alias) DO UPDATE SET tag=excluded.tag;
+        ")
+        .bind(&alias)
+        .bind(&name)
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(name)
+}
+
+pub(crate) async fn remove_alias(conn: &Pool<Sqlite>,
+    alias: &str,
+    name: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+    sqlx::query("DELETE FROM tag_aliases WHERE alias=? AND tag=?;")
+        .bind(alias)
+        .bind(name)
+        .execute(conn)
+        .await
+}
+
+// rewrites articles tagged by `from` to be tagged by `into` and makes `from`
+// an alias of `into`, both are resolved while holding the write lock,
+// returns canonical name of the tag merged into
+pub(crate) async fn merge(conn: &Pool<Sqlite>,
+    from: &str,
+    into: &str,
+) -> Result<String, errors::BackendError>  {
+    let mut tx = super::begin_immediate(conn).await?;
+    let from = canonical_in(&mut tx, from).await?;
+    let into = canonical_in(&mut tx, into).await?;
+    let (from, into) = (from.as_str(), into.as_str());
+
+    if from == into {
+        return Err(errors::BackendError::ValidationError(format!("tag {} cannot be merged into itself", from)));
+    }
+
+    let statement = format!("SELECT id, tagList FROM articles WHERE {}", HAS_TAG);
+    let articles = sqlx::query_as::<_, (i32, String)>(&statement)
+        .bind(from)
+        .fetch_all(&mut tx)
+        .await?;
+
+    for (id, tag_list) in &articles {
+        let mut tags = Vec::<String>::new();
+        for tag in tag_list.split(',').map(tag::normalize).filter(|tag| !tag.is_empty()) {
+            let tag = if tag == from { into.to_string() } else { tag };
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let tag_list = tags.iter().fold("".to_string(), |s, tag| format!("{}{},", s, tag));
+
+        sqlx::query("UPDATE articles SET tagList=? WHERE id=?;")
+            .bind(tag_list)
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+    }
+
+    sqlx::query(
+        "UPDATE tag_aliases SET tag=? WHERE tag=?;
+        INSERT INTO tag_aliases (alias, tag) VALUES(?, ?) ON CONFLICT(alias) DO UPDATE SET tag=excluded.tag;
+        UPDATE OR IGNORE followed_tags SET tag=? WHERE tag=?;
+        DELETE FROM followed_tags WHERE tag=?;
+        UPDATE OR IGNORE article_tags SET tag=? WHERE tag=?;
+        DELETE FROM article_tags WHERE tag=?;
+        DELETE FROM tags WHERE name=?;
+        ")
+        .bind(into)
+        .bind(from)
+        .bind(from)
+        .bind(into)
+        .bind(into)
+        .bind(from)
+        .bind(from)
+        .bind(into)
+        .bind(from)
+        .bind(from)
+        .bind(from)
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(into.to_string())
+}
+
+// indexes tags of a newly written article, tag_list being its stored tagList
+pub(crate) async fn index_article_tags(tx: &mut Transaction<'_, Sqlite>,
+    article_id: i64,
+    tag_list: &str,
+) -> Result<(), errors::BackendError>  {
+    for tag in tag_list.split(',').map(tag::normalize).filter(|tag| !tag.is_empty()) {
+        sqlx::query("INSERT OR IGNORE INTO article_tags (article_id, tag) VALUES(?, ?);")
+            .bind(article_id)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn get_tag_cloud(conn: &Pool<Sqlite>,
+    filter: filters::TagCloudFilter,
+    limit_offset: filters::LimitOffsetFilter,
+) -> Result<tag::TagCloud, errors::BackendError>  {
+    let statement = format!("\
+        SELECT article_tags.tag AS name, COUNT(*) AS articlesCount FROM article_tags \
+            INNER JOIN articles ON articles.id = article_tags.article_id \
+            WHERE {} \
+            GROUP BY article_tags.tag \
+            {} {}", 
+        filter, filter.order_by(), limit_offset);
+
+    let tags = sqlx::query_as::<_, tag::TagCount>(&statement)
+        .fetch_all(conn)
+        .await?;
+    Ok(tag::TagCloud { tags })
+}
+
+// most used tags starting with prefix, expected to be normalized,
+// answered from the index on article_tags.tag
+pub(crate) async fn autocomplete(conn: &Pool<Sqlite>,
+    prefix: &str,
+    limit: i32,
+) -> Result<Vec<String>, errors::BackendError>  {
+    let pattern = format!("{}%", prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_"));
+    let filter = filters::TagCloudFilter::default();
+
+    let statement = format!("\
+        SELECT article_tags.tag FROM article_tags \
+            INNER JOIN articles ON articles.id = article_tags.article_id \
+            WHERE article_tags.tag LIKE ? ESCAPE '\\' AND {} \
+            GROUP BY article_tags.tag \
+            ORDER BY COUNT(*) DESC, article_tags.tag ASC LIMIT ?", 
+        filter);
+
+    let tags = sqlx::query_scalar(&statement)
+        .bind(pattern)
+        .bind(limit)
+        .fetch_all(conn)
+        .await?;
+    Ok(tags)
+}
@@ -0,0 +1,140 @@
+use sqlx::{Pool, Transaction};
+use sqlx::sqlite::Sqlite;
+use crate::{models::upload, errors};
+
+// file reachable through its url, anywhere in profiles, articles or comments,
+// or being one of the avatar sizes
+const REFERENCED: &str = "( \
+    EXISTS (SELECT 1 FROM profiles WHERE instr(image, '/uploads/' || uploads.id)) \
+    OR EXISTS (SELECT 1 FROM avatars WHERE avatars.upload_id = uploads.id) \
+    OR EXISTS (SELECT 1 FROM articles WHERE instr(body, '/uploads/' || uploads.id)) \
+    OR EXISTS (SELECT 1 FROM comments WHERE instr(body, '/uploads/' || uploads.id)) \
+)";
+
+// records upload unless it would take bytes of the user's files over quota_bytes, 
+// other files are summed up within the same statement, so that concurrent uploads 
+// cannot exceed the quota together, uploading the same file again is not counted twice
+pub(crate) async fn add_upload(conn: &Pool<Sqlite>,
+    id: &str,
+    username: &str,
+    content_type: &str,
+    size: i64,
+    quota_bytes: i64,
+) -> Result<upload::Upload, errors::BackendError> {
+    let query_res = sqlx::query(
+        "INSERT INTO uploads (id, username, contentType, size, createdAt) 
+        SELECT ?, ?, ?, ?, datetime('now') 
+        WHERE (SELECT COALESCE(SUM(size), 0) FROM uploads WHERE username=? AND id<>?) + ? <= ?
+        ON CONFLICT (id, username) DO UPDATE SET createdAt=excluded.createdAt;
+        ")
+        .bind(id)
+        .bind(username)
+        .bind(content_type)
+        .bind(size)
+        .bind(username)
+        .bind(id)
+        .bind(size)
+        .bind(quota_bytes)
+        .execute(conn)
+        .await?;
+    if 0 == query_res.rows_affected() {
+        return Err(errors::BackendError::UploadTooLarge(
+            format!("upload quota of {} bytes exceeded", quota_bytes)));
+    }
+
+    get_upload(conn, id).await?
+        .ok_or(errors::BackendError::NoUploadFound(id.to_string()))
+}
+
+pub(crate) async fn get_upload(conn: &Pool<Sqlite>,
+    id: &str,
+) -> Result<Option<upload::Upload>, sqlx::Error> {
+    sqlx::query_as::<_, upload::Upload>(
+        "SELECT id, contentType, size, MIN(createdAt) as createdAt FROM uploads WHERE id=? GROUP BY id;")
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+}
+
+// uploads older than window_minutes which nothing links to, one per uploader
+pub(crate) async fn orphan_uploads(conn: &Pool<Sqlite>,
+    window_minutes: i64,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let statement = format!(
+        "SELECT id, username FROM uploads 
+        WHERE createdAt <= datetime('now', ?) AND NOT {};", REFERENCED);
+
+    sqlx::query_as::<_, (String, String)>(&statement)
+        .bind(format!("-{} minutes", window_minutes))
+        .fetch_all(conn)
+        .await
+}
+
+// deletes the upload of the user only if it is still an orphan,
+// returns whether no one else has uploaded the file, so that it can be removed
+pub(crate) async fn delete_orphan(tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+    username: &str,
+    window_minutes: i64,
+) -> Result<bool, sqlx::Error> {
+    let statement = format!(
+        "DELETE FROM uploads 
+        WHERE id=? AND username=? AND createdAt <= datetime('now', ?) AND NOT {};", REFERENCED);
+
+    let deleted = sqlx::query(&statement)
+        .bind(id)
+        .bind(username)
+        .bind(format!("-{} minutes", window_minutes))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query_as::<_, (bool,)>("SELECT NOT EXISTS (SELECT 1 FROM uploads WHERE id=?);")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map(|(unused,)| unused)
+}
+
+// avatar sizes replace the previous ones of the user
+pub(crate) async fn set_avatar(conn: &Pool<Sqlite>,
+    username: &str,
+    sizes: &[(u32, String)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("DELETE FROM avatars WHERE username=?;")
+        .bind(username)
+        .execute(&mut tx)
+        .await?;
+    for (size, upload_id) in sizes {
+        sqlx::query("INSERT INTO avatars (username, size, upload_id) VALUES(?, ?, ?);")
+            .bind(username)
+            .bind(size)
+            .bind(upload_id)
+            .execute(&mut tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+// smallest avatar not smaller than requested, or the largest one
+pub(crate) async fn get_avatar(conn: &Pool<Sqlite>,
+    username: &str,
+    size: u32,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_as::<_, (String,)>(
+        "SELECT upload_id FROM avatars WHERE username=? 
+        ORDER BY size < ?, CASE WHEN size < ? THEN -size ELSE size END 
+        LIMIT 1;
+        ")
+        .bind(username)
+        .bind(size)
+        .bind(size)
+        .fetch_optional(conn)
+        .await
+        .map(|row| row.map(|(upload_id,)| upload_id))
+}
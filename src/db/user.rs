@@ -1,5 +1,5 @@
-use sqlx::{Pool};
-use sqlx::sqlite::{Sqlite};
+use sqlx::{Pool, Transaction};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
 use crate::{models, filters, errors};
 
 pub(crate) async fn register_user(
@@ -50,17 +50,22 @@ pub(crate) async fn update_user(
     filter: filters::UpdateUserFilter<'_>
 ) -> Result<models::user::User, crate::errors::BackendError>  {
     
-    let statement = format!(
-        "UPDATE profiles SET {} WHERE user_id=(SELECT id FROM users WHERE {});\n
-         UPDATE users SET {} WHERE {} ", 
-        updated_user.profile, filter, updated_user, filter);
-
-    let query_res = sqlx::query(&statement)
-        .execute(conn)    
+    // version is bumped first, so that of concurrent updates based on 
+    // the same version only the one bumping it writes the profile
+    let mut tx = conn.begin().await?;
+    let statement = format!("UPDATE users SET {}, version=version+1 WHERE {} RETURNING id;", updated_user, filter);
+    let user_id: Option<i32> = sqlx::query_scalar(&statement)
+        .fetch_optional(&mut tx)
         .await?;
-    
 
-    if 0 < query_res.rows_affected() {    
+    if let Some(user_id) = user_id {
+        let statement = format!("UPDATE profiles SET {} WHERE user_id=?;", updated_user.profile);
+        sqlx::query(&statement)
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+
         let filter = filters::UserFilter {
             username: updated_user.username.or(filter.username),
             email: updated_user.email.or(filter.email),
@@ -80,8 +85,12 @@ pub(crate) async fn get_profile(
 
     let profile = sqlx::query_as::<_, models::user::Profile>(
         &format!(
-            "SELECT *, 
-                (SELECT COUNT(*)>0 FROM followers WHERE celeb_name = '{}') AS following
+            "SELECT profiles.username, bio, 
+                COALESCE(image, '/api/profiles/' || profiles.username || '/avatar') AS image,
+                (SELECT COUNT(*)>0 FROM followers WHERE celeb_name = '{}') AS following,
+                (SELECT COUNT(*) FROM followers WHERE celeb_name = profiles.username) AS followersCount,
+                (SELECT COUNT(*) FROM followers WHERE follower_name = profiles.username) AS followingCount,
+                (SELECT COUNT(*) FROM articles WHERE author = profiles.username AND deletedAt IS NULL) AS articlesCount
             FROM profiles 
             INNER JOIN users ON profiles.username = users.username 
             WHERE profiles.username = '{}';
@@ -95,21 +104,89 @@ pub(crate) async fn get_profile(
     profile
 }
 
+pub(crate) async fn get_following(conn: &Pool<Sqlite>,
+    follower_name: &str,
+) -> Result<Vec<String>, crate::errors::BackendError>  {
+
+    let following = sqlx::query_scalar("SELECT celeb_name FROM followers WHERE follower_name=?;")
+        .bind(follower_name)
+        .fetch_all(conn)    
+        .await?;
+    Ok(following)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FollowList {
+    Followers,
+    Following,
+}
+
+impl FollowList {
+    // column matched against listed user and column holding listed profiles
+    fn columns(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Followers => ("celeb_name", "follower_name"),
+            Self::Following => ("follower_name", "celeb_name"),
+        }
+    }
+}
+
+pub(crate) async fn get_follow_list(conn: &Pool<Sqlite>,
+    username: &str,
+    list: FollowList,
+    limit_offset: crate::filters::LimitOffsetFilter,
+) -> Result<Vec<models::user::Profile>, crate::errors::BackendError>  {
+    let (user_column, listed_column) = list.columns();
+
+    let statement = format!("SELECT {} FROM followers WHERE {}=? ORDER BY rowid DESC {}", 
+        listed_column, user_column, limit_offset);
+
+    let usernames: Vec<String> = sqlx::query_scalar(&statement)
+        .bind(username)
+        .fetch_all(conn)    
+        .await?;
+
+    let mut profiles = Vec::<models::user::Profile>::with_capacity(usernames.len());
+    for username in usernames {
+        if let Some(profile) = get_profile(conn, &username).await {
+            profiles.push(profile);
+        }
+    }
+    Ok(profiles)
+}
+
+pub(crate) async fn count_follow_list(conn: &Pool<Sqlite>,
+    username: &str,
+    list: FollowList,
+) -> Result<i64, crate::errors::BackendError>  {
+    let (user_column, _) = list.columns();
+
+    let statement = format!("SELECT COUNT(*) FROM followers WHERE {}=?", user_column);
+
+    let count = sqlx::query_scalar(&statement)
+        .bind(username)
+        .fetch_one(conn)    
+        .await?;
+    Ok(count)
+}
+
 pub(crate) async fn follow(conn: &Pool<Sqlite>,
     follower_name: &str,
     celeb_name: &str,
-) -> Result<models::user::Profile, crate::errors::BackendError>  {
+) -> Result<(models::user::Profile, u64), crate::errors::BackendError>  {
     
-    sqlx::query("INSERT INTO followers (follower_name, celeb_name)
+    let query_res = sqlx::query("INSERT INTO followers (follower_name, celeb_name)
         VALUES( ?,?) ON CONFLICT DO NOTHING;")
         .bind(follower_name)
         .bind(celeb_name)
         .execute(conn)    
         .await?;
 
-    get_profile(conn, celeb_name)
+    let profile = get_profile(conn, celeb_name)
         .await
-        .ok_or(crate::errors::BackendError::NoUserFound(celeb_name.to_string()))
+        .ok_or(crate::errors::BackendError::NoUserFound(celeb_name.to_string()))?;
+    // rows affected tell whether the user has been followed just now
+    Ok((profile, query_res.rows_affected()))
 }
 
 pub(crate) async fn unfollow(conn: &Pool<Sqlite>,
@@ -127,3 +204,127 @@ pub(crate) async fn unfollow(conn: &Pool<Sqlite>,
         .ok_or(crate::errors::BackendError::NoUserFound(celeb_name.to_string()))
 
 }
+
+// blocking removes following in both directions
+pub(crate) async fn block(conn: &Pool<Sqlite>,
+    blocker_name: &str,
+    blocked_name: &str,
+) -> Result<models::user::Profile, crate::errors::BackendError>  {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("INSERT INTO blocks (blocker_name, blocked_name)
+        VALUES( ?,?) ON CONFLICT DO NOTHING;")
+        .bind(blocker_name)
+        .bind(blocked_name)
+        .execute(&mut tx)    
+        .await?;
+
+    sqlx::query("DELETE FROM followers WHERE 
+        (follower_name=? AND celeb_name=?) OR (follower_name=? AND celeb_name=?);")
+        .bind(blocker_name)
+        .bind(blocked_name)
+        .bind(blocked_name)
+        .bind(blocker_name)
+        .execute(&mut tx)    
+        .await?;
+    tx.commit().await?;
+
+    get_profile(conn, blocked_name)
+        .await
+        .ok_or(crate::errors::BackendError::NoUserFound(blocked_name.to_string()))
+}
+
+pub(crate) async fn unblock(conn: &Pool<Sqlite>,
+    blocker_name: &str,
+    blocked_name: &str,
+) -> Result<models::user::Profile, crate::errors::BackendError>  {
+
+    sqlx::query("DELETE FROM blocks WHERE blocker_name=? AND blocked_name=?;")
+        .bind(blocker_name)
+        .bind(blocked_name)
+        .execute(conn)    
+        .await?;
+
+    get_profile(conn, blocked_name)
+        .await
+        .ok_or(crate::errors::BackendError::NoUserFound(blocked_name.to_string()))
+}
+
+pub(crate) async fn is_blocked(conn: &Pool<Sqlite>,
+    blocker_name: &str,
+    blocked_name: &str,
+) -> Result<bool, crate::errors::BackendError>  {
+
+    let blocked = sqlx::query_scalar("SELECT COUNT(*)>0 FROM blocks WHERE blocker_name=? AND blocked_name=?;")
+        .bind(blocker_name)
+        .bind(blocked_name)
+        .fetch_one(conn)    
+        .await?;
+    Ok(blocked)
+}
+
+pub(crate) async fn mute(conn: &Pool<Sqlite>,
+    muter_name: &str,
+    muted_name: &str,
+) -> Result<models::user::Profile, crate::errors::BackendError>  {
+
+    sqlx::query("INSERT INTO mutes (muter_name, muted_name)
+        VALUES( ?,?) ON CONFLICT DO NOTHING;")
+        .bind(muter_name)
+        .bind(muted_name)
+        .execute(conn)    
+        .await?;
+
+    get_profile(conn, muted_name)
+        .await
+        .ok_or(crate::errors::BackendError::NoUserFound(muted_name.to_string()))
+}
+
+pub(crate) async fn unmute(conn: &Pool<Sqlite>,
+    muter_name: &str,
+    muted_name: &str,
+) -> Result<models::user::Profile, crate::errors::BackendError>  {
+
+    sqlx::query("DELETE FROM mutes WHERE muter_name=? AND muted_name=?;")
+        .bind(muter_name)
+        .bind(muted_name)
+        .execute(conn)    
+        .await?;
+
+    get_profile(conn, muted_name)
+        .await
+        .ok_or(crate::errors::BackendError::NoUserFound(muted_name.to_string()))
+}
+
+pub(crate) async fn suspend(tx: &mut Transaction<'_, Sqlite>,
+    username: &str,
+) -> Result<SqliteQueryResult, sqlx::Error>  {
+
+    sqlx::query("UPDATE users SET suspended=1 WHERE username=?;")
+        .bind(username)
+        .execute(tx)    
+        .await
+}
+
+pub(crate) async fn is_suspended(conn: &Pool<Sqlite>,
+    username: &str,
+) -> Result<bool, crate::errors::BackendError>  {
+
+    let suspended = sqlx::query_scalar("SELECT COUNT(*)>0 FROM users WHERE username=? AND suspended=1;")
+        .bind(username)
+        .fetch_one(conn)    
+        .await?;
+    Ok(suspended)
+}
+
+// image as set by the user, without generated fallback
+pub(crate) async fn get_image(conn: &Pool<Sqlite>,
+    username: &str,
+) -> Result<Option<String>, crate::errors::BackendError> {
+    sqlx::query_as::<_, (Option<String>,)>("SELECT image FROM profiles WHERE username=?;")
+        .bind(username)
+        .fetch_optional(conn)
+        .await?
+        .map(|(image,)| image)
+        .ok_or(crate::errors::BackendError::NoUserFound(username.to_string()))
+}
@@ -0,0 +1,183 @@
+use sqlx::{Pool};
+use sqlx::sqlite::{Sqlite, SqliteQueryResult};
+use crate::{models::webhook, filters, errors};
+
+pub(crate) async fn create_webhook(conn: &Pool<Sqlite>,
+    owner: &str,
+    url: &str,
+    secret: &str,
+    events: &[webhook::WebhookEvent],
+) -> Result<webhook::WebhookResponse, errors::BackendError>  {
+    let mut tx = conn.begin().await?;
+
+    let id = sqlx::query("INSERT INTO webhooks (owner, url, secret, createdAt) VALUES(?, ?, ?, datetime('now'));")
+        .bind(owner)
+        .bind(url)
+        .bind(secret)
+        .execute(&mut tx)
+        .await?
+        .last_insert_rowid() as i32;
+
+    for event in events {
+        sqlx::query("INSERT INTO webhook_events (webhook_id, event) VALUES(?, ?) ON CONFLICT DO NOTHING;")
+            .bind(id)
+            .bind(event)
+            .execute(&mut tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let filter = filters::WebhookFilter::default().id(id);
+    get_webhooks(conn, &filter)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(errors::BackendError::NoWebhookFound(id))
+}
+
+pub(crate) async fn get_webhooks(conn: &Pool<Sqlite>,
+    filter: &filters::WebhookFilter,
+) -> Result<Vec<webhook::WebhookResponse>, errors::BackendError>  {
+
+    let statement = format!("SELECT * FROM webhooks WHERE {} ORDER BY id ASC", filter);
+
+    let webhooks = sqlx::query_as::<_, webhook::Webhook>(&statement)
+        .fetch_all(conn)
+        .await?;
+
+    let mut webhook_responses = Vec::<webhook::WebhookResponse>::with_capacity(webhooks.len());
+
+    for webhook in webhooks {
+        let events = sqlx::query_scalar("SELECT event FROM webhook_events WHERE webhook_id=?;")
+            .bind(webhook.id)
+            .fetch_all(conn)
+            .await?;
+        webhook_responses.push( webhook::WebhookResponse { webhook, events } );
+    }
+    Ok(webhook_responses)
+}
+
+pub(crate) async fn delete_webhook(conn: &Pool<Sqlite>,
+    filter: &filters::WebhookFilter,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let statement = format!("DELETE FROM webhooks WHERE {}", filter);
+
+    sqlx::query(&statement)
+        .execute(conn)
+        .await
+}
+
+// queues delivery of event to webhooks subscribed to it and owned 
+// by one of the users the event is about
+pub(crate) async fn enqueue(conn: &Pool<Sqlite>,
+    event: webhook::WebhookEvent,
+    owners: &[&str],
+    payload: &str,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let statement = format!("\
+        INSERT INTO webhook_deliveries \
+            (webhookId, event, payload, status, attempts, createdAt, nextAttemptAt) \
+        SELECT webhook_id, event, ?, ?, 0, datetime('now'), datetime('now') \
+            FROM webhook_events JOIN webhooks ON webhooks.id = webhook_events.webhook_id \
+            WHERE event=? AND owner IN ({});",
+        vec!["?"; owners.len()].join(","));
+
+    let mut query = sqlx::query(&statement)
+        .bind(payload)
+        .bind(webhook::DeliveryStatus::Pending)
+        .bind(event);
+    for owner in owners {
+        query = query.bind(*owner);
+    }
+    query
+        .execute(conn)
+        .await
+}
+
+pub(crate) async fn due_deliveries(conn: &Pool<Sqlite>,
+    limit: i32,
+) -> Result<Vec<webhook::PendingDelivery>, sqlx::Error> {
+
+    sqlx::query_as::<_, webhook::PendingDelivery>(
+        "SELECT webhook_deliveries.id AS id, event, payload, attempts, url, secret 
+            FROM webhook_deliveries JOIN webhooks ON webhooks.id = webhook_deliveries.webhookId
+            WHERE status=? AND nextAttemptAt <= datetime('now')
+            ORDER BY webhook_deliveries.id ASC LIMIT ?;
+        ")
+        .bind(webhook::DeliveryStatus::Pending)
+        .bind(limit)
+        .fetch_all(conn)
+        .await
+}
+
+pub(crate) async fn delivery_succeeded(conn: &Pool<Sqlite>,
+    id: i32,
+    response_status: i32,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status=?, attempts=attempts+1, responseStatus=?, 
+            lastError=NULL, nextAttemptAt=NULL, deliveredAt=datetime('now') 
+        WHERE id=?;
+        ")
+        .bind(webhook::DeliveryStatus::Delivered)
+        .bind(response_status)
+        .bind(id)
+        .execute(conn)
+        .await
+}
+
+// failed attempt is retried after retry_in_seconds, 
+// None gives up on the delivery
+pub(crate) async fn delivery_failed(conn: &Pool<Sqlite>,
+    id: i32,
+    response_status: Option<i32>,
+    error: &str,
+    retry_in_seconds: Option<i64>,
+) -> Result<SqliteQueryResult, sqlx::Error> {
+
+    let status = match retry_in_seconds {
+        Some(_) => webhook::DeliveryStatus::Pending,
+        None => webhook::DeliveryStatus::Failed,
+    };
+
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status=?, attempts=attempts+1, responseStatus=?, lastError=?, 
+            nextAttemptAt=datetime('now', ?) 
+        WHERE id=?;
+        ")
+        .bind(status)
+        .bind(response_status)
+        .bind(error)
+        .bind(retry_in_seconds.map(|seconds| format!("+{} seconds", seconds)))
+        .bind(id)
+        .execute(conn)
+        .await
+}
+
+pub(crate) async fn get_deliveries(conn: &Pool<Sqlite>,
+    webhook_id: i32,
+    limit_offset: filters::LimitOffsetFilter,
+) -> Result<Vec<webhook::WebhookDelivery>, sqlx::Error> {
+
+    let statement = format!("\
+        SELECT * FROM webhook_deliveries WHERE webhookId=? ORDER BY id DESC {}", 
+        limit_offset);
+
+    sqlx::query_as::<_, webhook::WebhookDelivery>(&statement)
+        .bind(webhook_id)
+        .fetch_all(conn)
+        .await
+}
+
+pub(crate) async fn count_deliveries(conn: &Pool<Sqlite>,
+    webhook_id: i32,
+) -> Result<i64, sqlx::Error> {
+
+    sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries WHERE webhookId=?;")
+        .bind(webhook_id)
+        .fetch_one(conn)
+        .await
+}
@@ -1,7 +1,8 @@
 use tide::prelude::*;
 
 use crate::app::AppState;
-use crate::{requests::{user::*, article::*}, filters};
+use crate::{requests::{user::*, article::*, event::*, webhook::*, report::*, tag::*, upload::*}, filters,
+    models::{reaction::ReactionKind, notification::NotificationPreferencesWrapped, upload::Avatar}, errors::BackendError};
 
 pub(crate) type Request = tide::Request<AppState>;
 
@@ -29,28 +30,57 @@ pub(crate) async fn current_user(req: Request) -> tide::Result {
     let token = crate::utils::token_from_request(&req)?;
 
     req.state().server.user_by_token(token).await
-        .and_then(|user| 
-            Ok(json!(user.wrap()).into())
+        .map(|user| 
+            crate::utils::with_etag(user.version, json!(user.wrap()))
         )
         .or_else(|err| err.into())
 }
               
 pub(crate) async fn profile(req: Request) -> tide::Result {
     let username = req.param("username")?;
-    req.state().server.profile(username).await
+    let token = crate::utils::optional_token_from_request(&req);
+
+    req.state().server.profile(token, username).await
         .and_then(|profile| 
             Ok(json!(profile.wrap()).into())
         )
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn followers(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    let token = crate::utils::optional_token_from_request(&req);
+
+    req.state().server.followers(token, username, limit_offset).await
+        .map(|profiles| 
+            json!(profiles).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn following(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    let token = crate::utils::optional_token_from_request(&req);
+
+    req.state().server.following(token, username, limit_offset).await
+        .map(|profiles| 
+            json!(profiles).into()
+        )
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn update_user(mut req: Request) -> tide::Result {
-    let update_user: UserUpdateWrapped = req.body_json().await?;
+    let mut update_user: UserUpdateWrapped = req.body_json().await?;
     let token = crate::utils::token_from_request(&req)?;
+    update_user.user.if_match = crate::utils::if_match_from_request(&req)?;
 
     req.state().server.update_user(token, update_user.user).await
-        .and_then(|user| 
-            Ok(json!(user.wrap()).into())
+        .map(|user| 
+            crate::utils::with_etag(user.version, json!(user.wrap()))
         )
         .or_else(|err| err.into())
 }
@@ -77,6 +107,50 @@ pub(crate) async fn unfollow(req: Request) -> tide::Result {
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn block(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.block(token, username).await
+        .map(|profile| 
+            json!(profile.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unblock(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.unblock(token, username).await
+        .map(|profile| 
+            json!(profile.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn mute(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.mute(token, username).await
+        .map(|profile| 
+            json!(profile.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unmute(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.unmute(token, username).await
+        .map(|profile| 
+            json!(profile.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn create_article(mut req: Request) -> tide::Result {
     let article_req_wrapped: CreateArticleRequestWrapped = req.body_json().await?;
     let token = crate::utils::token_from_request(&req)?;
@@ -90,36 +164,69 @@ pub(crate) async fn create_article(mut req: Request) -> tide::Result {
 
 pub(crate) async fn get_article(req: Request) -> tide::Result {
     let slug = req.param("slug")?;
-
-    req.state().server.get_article_by_slug(slug).await
-        .and_then(|article_response| 
-            Ok(json!(article_response.wrap()).into())
+    let token = crate::utils::optional_token_from_request(&req);
+    let context = crate::utils::view_context_from_request(&req);
+    let body_format: filters::BodyFormatFilter = req.query()?;
+    let server = &req.state().server;
+
+    let res = match server.view_article(token, slug, &context).await {
+        Ok(article_response) => server.render_article(article_response, body_format.format).await,
+        err => err,
+    };
+    res
+        .map(|article_response| 
+            // article requested by its old slug is redirected to the current one
+            if article_response.article.slug != slug {
+                tide::Redirect::permanent(
+                    format!("/api/articles/{}", article_response.article.slug)).into()
+            } else {
+                crate::utils::with_etag(article_response.article.version, json!(article_response.wrap()))
+            }
         )
         .or_else(|err| err.into())
 }
 
 pub(crate) async fn get_articles(req: Request) -> tide::Result {
     let filter: filters::ArticleFilterByValues = req.query()?;
-    let order_by = filters::OrderByFilter::Descending("updatedAt");
+    let sort: filters::ArticleSortFilter = req.query()?;
+    let order_by = sort.order_by();
     let limit_offset: filters::LimitOffsetFilter = req.query()?;
+    let token = crate::utils::optional_token_from_request(&req);
+    let body_format: filters::BodyFormatFilter = req.query()?;
+    let server = &req.state().server;
 
-    req.state().server.get_articles(filter, order_by, limit_offset).await
+    let res = match server.get_articles(token, filter, order_by, limit_offset).await {
+        Ok(articles) => server.render_articles(articles, body_format.format).await,
+        err => err,
+    };
+    res
         .and_then(|multiple_articles_response| 
             Ok(json!(multiple_articles_response).into())
         )
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn related_articles(req: Request) -> tide::Result {
+    let slug = req.param("slug")?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+    let token = crate::utils::optional_token_from_request(&req);
+
+    req.state().server.related_articles(token, slug, limit_offset).await
+        .map(|articles| json!(articles).into())
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn update_article(mut req: Request) -> tide::Result {
     let update_article_req_body: UpdateArticleRequestBody = req.body_json().await?;
     let token = crate::utils::token_from_request(&req)?;
 
     let slug = req.param("slug")?;
-    let update_article_req = UpdateArticleRequest::from_req_parts(update_article_req_body, slug);
+    let if_match = crate::utils::if_match_from_request(&req)?;
+    let update_article_req = UpdateArticleRequest::from_req_parts(update_article_req_body, slug, if_match);
 
     req.state().server.update_article(token, update_article_req).await
-        .and_then(|articles_response| 
-            Ok(json!(articles_response.wrap()).into())
+        .map(|articles_response| 
+            crate::utils::with_etag(articles_response.article.version, json!(articles_response.wrap()))
         )
         .or_else(|err| err.into())
 }
@@ -135,6 +242,15 @@ pub(crate) async fn delete_article(req: Request) -> tide::Result {
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn restore_article(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+
+    req.state().server.restore_article(token, slug).await
+        .map(|article_response| json!(article_response.wrap()).into())
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn favorite_article(req: Request) -> tide::Result {
     let token = crate::utils::token_from_request(&req)?;
 
@@ -159,11 +275,54 @@ pub(crate) async fn unfavorite_article(req: Request) -> tide::Result {
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn bookmark_article(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+
+    req.state().server.bookmark_article(token, slug).await
+        .map(|article_response| json!(article_response.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unbookmark_article(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+
+    req.state().server.unbookmark_article(token, slug).await
+        .map(|article_response| json!(article_response.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn user_stats(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let filter: filters::StatsFilter = req.query()?;
+
+    req.state().server.author_stats(token, filter).await
+        .map(|stats| json!(stats.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn bookmarks(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    req.state().server.bookmarks(token, limit_offset).await
+        .map(|articles| json!(articles).into())
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn feed_articles(req: Request) -> tide::Result {
     let token = crate::utils::token_from_request(&req)?;
+    let feed_filter: filters::FeedFilter = req.query()?;
     let limit_offset: filters::LimitOffsetFilter = req.query()?;
+    let body_format: filters::BodyFormatFilter = req.query()?;
+    let server = &req.state().server;
 
-    req.state().server.feed_articles(token, limit_offset).await
+    let res = match server.feed_articles(token, feed_filter, limit_offset).await {
+        Ok(articles) => server.render_articles(articles, body_format.format).await,
+        err => err,
+    };
+    res
         .and_then(|articles| 
             Ok(json!(articles).into())
         )
@@ -172,10 +331,15 @@ pub(crate) async fn feed_articles(req: Request) -> tide::Result {
 
 pub(crate) async fn get_comments(req: Request) -> tide::Result {
     let slug = req.param("slug")?;
+    let thread_filter: filters::CommentThreadFilter = req.query()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+    let token = crate::utils::optional_token_from_request(&req);
+    let body_format: filters::BodyFormatFilter = req.query()?;
+    let server = &req.state().server;
 
-    req.state().server.get_comments(slug).await
+    server.get_comments(token, slug, thread_filter, limit_offset).await
         .and_then(|comments| 
-            Ok(json!(comments).into())
+            Ok(json!(server.render_comments(comments, body_format.format)).into())
         )
         .or_else(|err| err.into())
 }
@@ -211,7 +375,41 @@ pub(crate) async fn delete_comment(req: Request) -> tide::Result {
         .or_else(|err| err.into())
 }
 
+pub(crate) async fn restore_comment(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+    let article_slug = req.param("slug")?;
+
+    req.state().server.restore_comment(token, article_slug, id).await
+        .map(|comment_response| json!(comment_response.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn update_comment(mut req: Request) -> tide::Result {
+    let wrapped: UpdateCommentRequestBodyWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+    let article_slug = req.param("slug")?;
+
+    let update_comment_req = UpdateCommentRequest::from_req_parts(wrapped, id, article_slug);
+
+    req.state().server.update_comment(token, update_comment_req).await
+        .map(|comment| 
+            json!(comment.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
 pub(crate) async fn get_tags(req: Request) -> tide::Result {
+    let prefix_filter: filters::TagPrefixFilter = req.query()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    if let Some(prefix) = prefix_filter.prefix {
+        return req.state().server.autocomplete_tags(&prefix, limit_offset).await
+            .map(|tags| json!(tags).into())
+            .or_else(|err| err.into());
+    }
+
     req.state().server.get_tags()
         .await 
         .and_then(|tags|
@@ -219,3 +417,390 @@ pub(crate) async fn get_tags(req: Request) -> tide::Result {
         )
         .or_else(|err| err.into())
 }
+
+pub(crate) async fn tag_cloud(req: Request) -> tide::Result {
+    let filter: filters::TagCloudFilter = req.query()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    req.state().server.tag_cloud(filter, limit_offset).await
+        .map(|tag_cloud| json!(tag_cloud).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn get_tag(req: Request) -> tide::Result {
+    let name = req.param("tag")?;
+
+    req.state().server.get_tag(name).await
+        .map(|tag| json!(tag.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn update_tag(mut req: Request) -> tide::Result {
+    let wrapped: UpdateTagRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let name = req.param("tag")?;
+
+    req.state().server.update_tag(token, name, wrapped.tag).await
+        .map(|tag| json!(tag.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn add_tag_alias(mut req: Request) -> tide::Result {
+    let wrapped: TagAliasRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let name = req.param("tag")?;
+
+    req.state().server.add_tag_alias(token, name, wrapped.alias).await
+        .map(|tag| json!(tag.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn remove_tag_alias(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let name = req.param("tag")?;
+    let alias = req.param("alias")?;
+
+    req.state().server.remove_tag_alias(token, name, alias).await
+        .map(|tag| json!(tag.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn merge_tags(mut req: Request) -> tide::Result {
+    let wrapped: MergeTagRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let name = req.param("tag")?;
+
+    req.state().server.merge_tags(token, name, wrapped.merge).await
+        .map(|tag| json!(tag.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn follow_tag(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let tag = req.param("tag")?;
+
+    req.state().server.follow_tag(token, tag).await
+        .map(|tags| json!(tags).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unfollow_tag(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let tag = req.param("tag")?;
+
+    req.state().server.unfollow_tag(token, tag).await
+        .map(|tags| json!(tags).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn followed_tags(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.followed_tags(token).await
+        .map(|tags| json!(tags).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn react_to_article(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+    let kind = match req.param("kind")?.parse::<ReactionKind>() {
+        Ok(kind) => kind,
+        Err(err) => return err.into(),
+    };
+
+    req.state().server.react_to_article(token, slug, kind).await
+        .map(|article_response| 
+            json!(article_response.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unreact_to_article(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+    let kind = match req.param("kind")?.parse::<ReactionKind>() {
+        Ok(kind) => kind,
+        Err(err) => return err.into(),
+    };
+
+    req.state().server.unreact_to_article(token, slug, kind).await
+        .map(|article_response| 
+            json!(article_response.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn react_to_comment(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+    let id = req.param("id")?.parse::<i32>()?;
+    let kind = match req.param("kind")?.parse::<ReactionKind>() {
+        Ok(kind) => kind,
+        Err(err) => return err.into(),
+    };
+
+    req.state().server.react_to_comment(token, slug, id, kind).await
+        .map(|comment| 
+            json!(comment.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unreact_to_comment(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+    let id = req.param("id")?.parse::<i32>()?;
+    let kind = match req.param("kind")?.parse::<ReactionKind>() {
+        Ok(kind) => kind,
+        Err(err) => return err.into(),
+    };
+
+    req.state().server.unreact_to_comment(token, slug, id, kind).await
+        .map(|comment| 
+            json!(comment.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn get_notifications(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let filter: filters::NotificationFilter = req.query()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    req.state().server.get_notifications(token, filter, limit_offset).await
+        .map(|notifications| 
+            json!(notifications).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn unread_notifications_count(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.unread_notifications_count(token).await
+        .map(|unread_count| 
+            json!(unread_count).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn mark_notification_read(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+
+    req.state().server.mark_notification_read(token, id).await
+        .map(|notification| 
+            json!(notification.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn mark_all_notifications_read(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.mark_all_notifications_read(token).await
+        .map(|unread_count| 
+            json!(unread_count).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn notification_preferences(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.notification_preferences(token).await
+        .map(|preferences| 
+            json!(preferences.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn update_notification_preferences(mut req: Request) -> tide::Result {
+    let wrapped: NotificationPreferencesWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.update_notification_preferences(token, wrapped.preferences).await
+        .map(|preferences| 
+            json!(preferences.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn events(mut req: Request) -> tide::Result {
+    let stream_req: EventStreamRequest = req.query()?;
+    let token = match crate::utils::optional_token_from_request(&req) {
+        Some(token) => token.to_string(),
+        None => stream_req.token
+            .ok_or(tide::Error::from_str(tide::StatusCode::Unauthorized, "no token in request"))?,
+    };
+    let last_event_id = crate::utils::last_event_id_from_request(&req);
+
+    let subscription = match req.state().server.subscribe(&token, stream_req.article, last_event_id).await {
+        Ok(subscription) => subscription,
+        Err(err) => return err.into(),
+    };
+    req.set_ext(subscription);
+
+    Ok(tide::sse::upgrade(req, |mut req: Request, sender| async move {
+        let subscription = req.ext_mut::<crate::events::Subscription>().unwrap();
+
+        while let Some(event) = subscription.next().await {
+            sender.send(event.kind.as_str(), &event.payload, Some(&event.id.to_string())).await?;
+        }
+        Ok(())
+    }))
+}
+
+pub(crate) async fn register_webhook(mut req: Request) -> tide::Result {
+    let wrapped: CreateWebhookRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.register_webhook(token, wrapped.webhook).await
+        .map(|webhook| 
+            json!(webhook.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn get_webhooks(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.get_webhooks(token).await
+        .map(|webhooks| 
+            json!(webhooks).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn delete_webhook(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+
+    req.state().server.delete_webhook(token, id).await
+        .map(|()| 
+            json!(()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn webhook_deliveries(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    req.state().server.webhook_deliveries(token, id, limit_offset).await
+        .map(|deliveries| 
+            json!(deliveries).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn report_article(mut req: Request) -> tide::Result {
+    let wrapped: ReportRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+
+    req.state().server.report_article(token, slug, wrapped.report).await
+        .map(|report| 
+            json!(report.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn report_comment(mut req: Request) -> tide::Result {
+    let wrapped: ReportRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let slug = req.param("slug")?;
+    let id = req.param("id")?.parse::<i32>()?;
+
+    req.state().server.report_comment(token, slug, id, wrapped.report).await
+        .map(|report| 
+            json!(report.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn get_reports(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let filter: filters::ReportFilter = req.query()?;
+    let limit_offset: filters::LimitOffsetFilter = req.query()?;
+
+    req.state().server.get_reports(token, filter, limit_offset).await
+        .map(|reports| 
+            json!(reports).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn dismiss_report(req: Request) -> tide::Result {
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+
+    req.state().server.dismiss_report(token, id).await
+        .map(|report| 
+            json!(report.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn moderate_report(mut req: Request) -> tide::Result {
+    let wrapped: ModerationRequestWrapped = req.body_json().await?;
+    let token = crate::utils::token_from_request(&req)?;
+    let id = req.param("id")?.parse::<i32>()?;
+
+    req.state().server.moderate_report(token, id, wrapped.moderation).await
+        .map(|report| 
+            json!(report.wrap()).into()
+        )
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn upload(mut req: Request) -> tide::Result {
+    let data = crate::utils::file_from_multipart(&mut req).await?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.upload(token, UploadRequest { data }).await
+        .map(|upload| json!(upload.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn set_avatar(mut req: Request) -> tide::Result {
+    let data = crate::utils::file_from_multipart(&mut req).await?;
+    let token = crate::utils::token_from_request(&req)?;
+
+    req.state().server.set_avatar(token, UploadRequest { data }).await
+        .map(|user| json!(user.wrap()).into())
+        .or_else(|err| err.into())
+}
+
+pub(crate) async fn avatar(req: Request) -> tide::Result {
+    let username = req.param("username")?;
+    let filter: filters::AvatarFilter = req.query()?;
+
+    match req.state().server.avatar(username, filter).await {
+        Ok(Avatar::Url(url)) => Ok(tide::Redirect::temporary(url).into()),
+        Ok(Avatar::Generated(png)) => Ok(tide::Response::builder(tide::StatusCode::Ok)
+            .body(png)
+            .content_type(http_types::mime::PNG)
+            .header("Cache-Control", "public, max-age=86400")
+            .build()),
+        Err(err) => err.into(),
+    }
+}
+
+// content never changes under its hash, so it can be cached for good
+pub(crate) async fn serve_upload(req: Request) -> tide::Result {
+    let id = req.param("id")?;
+
+    match req.state().server.get_upload(id).await {
+        Ok((upload, data)) => Ok(tide::Response::builder(tide::StatusCode::Ok)
+            .body(data)
+            .content_type(http_types::Mime::from(upload.content_type.as_str()))
+            .header("X-Content-Type-Options", "nosniff")
+            .header("Cache-Control", "public, max-age=31536000, immutable")
+            .build()),
+        Err(BackendError::NoUploadFound(_)) => Ok(tide::Response::new(tide::StatusCode::NotFound)),
+        Err(err) => err.into(),
+    }
+}
@@ -6,6 +6,7 @@ const SQLITE_CONSTRAINT_UNIQUE: &str = "2067";
 #[derive(Debug, Serialize)]
 pub enum BackendError {
     UsernameOrEmailExists,
+    ArticleSlugExists,
     TokenCreationFailure(String),
     ValidationError(String),
     AuthenticationFailure,
@@ -15,6 +16,13 @@ pub enum BackendError {
     NoArticleFound,
     NoCommentFound(i32),
     NoCommentAdded,
+    NoNotificationFound(i32),
+    NoWebhookFound(i32),
+    NoReportFound(i32),
+    NoTagFound(String),
+    NoUploadFound(String),
+    UploadTooLarge(String),
+    PreconditionFailed,
     UnhandledDBError(String, String),
     WebServerConnectionFailure(String),
     UnexpectedError(String),
@@ -25,6 +33,7 @@ impl std::fmt::Display for BackendError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { 
             match self {
                 Self::UsernameOrEmailExists => write!( f, "{}", "username or email is already taken"),
+                Self::ArticleSlugExists => write!( f, "article slug is already taken"),
                 Self::IncorrectUsernameOrPassword(email) => write!( f, "{}", format!("incorrect credentials for {}", email)),
                 Self::TokenCreationFailure(message) => write!( f, "{}", format!("JWT not created, reason: {}", message)),
                 Self::ValidationError(message) => write!( f, "{}", message),
@@ -34,6 +43,13 @@ impl std::fmt::Display for BackendError {
                 Self::NoArticleFound => write!( f, "{}", "article not found"),
                 Self::NoCommentFound(id) => write!( f, "{}", format!("comment with id {} not found", id)),
                 Self::NoCommentAdded => write!( f, "{}", "no comment added"),
+                Self::NoNotificationFound(id) => write!( f, "notification with id {} not found", id),
+                Self::NoWebhookFound(id) => write!( f, "webhook with id {} not found", id),
+                Self::NoReportFound(id) => write!( f, "report with id {} not found", id),
+                Self::NoTagFound(name) => write!( f, "tag {} not found", name),
+                Self::NoUploadFound(id) => write!( f, "upload {} not found", id),
+                Self::UploadTooLarge(message) => write!( f, "{}", message),
+                Self::PreconditionFailed => write!( f, "resource has been modified since it was retrieved"),
                 Self::UnhandledDBError(msg, code) =>  
                     write!( f, "{}", format!("Unhandled db error: {}, code: {}", msg, code)),
                 Self::UnexpectedError(msg) => 
@@ -58,6 +74,8 @@ impl Into<tide::Result> for BackendError {
             |
             Self::UsernameOrEmailExists 
             |
+            Self::ArticleSlugExists 
+            |
             Self::IncorrectUsernameOrPassword(_)
             |
             Self::NoUserFound(_)  
@@ -66,6 +84,16 @@ impl Into<tide::Result> for BackendError {
             |
             Self::NoCommentAdded
             |
+            Self::NoNotificationFound(_)
+            |
+            Self::NoWebhookFound(_)
+            |
+            Self::NoReportFound(_)
+            |
+            Self::NoTagFound(_)
+            |
+            Self::NoUploadFound(_)
+            |
             Self::NoCommentFound(_) => {
                 Ok(tide::Response::from(json!({ "errors":{"body": [ message ] }})))    
             }
@@ -76,6 +104,8 @@ impl Into<tide::Result> for BackendError {
                     json!({ "errors":{"body": [ message ] }}))),
             Self::AuthenticationFailure => Err(tide::Error::from_str(tide::StatusCode::Unauthorized, self.to_string())),
             Self::Forbidden => Err(tide::Error::from_str(tide::StatusCode::Forbidden, self.to_string())),
+            Self::UploadTooLarge(_) => Err(tide::Error::from_str(tide::StatusCode::PayloadTooLarge, self.to_string())),
+            Self::PreconditionFailed => Err(tide::Error::from_str(tide::StatusCode::PreconditionFailed, self.to_string())),
             Self::UnexpectedError(_) => Err(tide::Error::from_str(tide::StatusCode::InternalServerError, self.to_string())),
             Self::WebServerConnectionFailure(_) => unreachable!(),
         }
@@ -93,8 +123,12 @@ impl From<sqlx::Error> for BackendError {
         match err {
             sqlx::Error::Database(ref db_err) => {
                 let code = db_err.code().unwrap().into_owned();
-                if DB_UNIQUE_CONSTRAINT_VIOLATION == code 
-                    || SQLITE_CONSTRAINT_UNIQUE == code {
+                let unique_violation = DB_UNIQUE_CONSTRAINT_VIOLATION == code 
+                    || SQLITE_CONSTRAINT_UNIQUE == code;
+                if unique_violation 
+                    && ["articles.slug", "slug_history.slug"].iter().any(|index| db_err.message().contains(index)) {
+                        BackendError::ArticleSlugExists
+                } else if unique_violation {
                         BackendError::UsernameOrEmailExists
                 } else {
                     BackendError::UnhandledDBError(
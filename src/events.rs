@@ -0,0 +1,100 @@
+// in-process broadcast of events produced by mutating server requests,
+// every subscriber receives all events and picks the ones addressed to it
+use std::sync::{Arc, Mutex};
+use std::collections::{HashSet, VecDeque};
+use async_std::channel::{self, Sender, Receiver, TrySendError};
+
+use crate::models::event::{Event, EventKind};
+
+const SUBSCRIBER_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<Event>>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Receiver<Event> {
+        let (sender, receiver) = channel::bounded(SUBSCRIBER_CAPACITY);
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    pub fn publish(&self, event: Event) {
+        // subscribers not keeping up lose events and catch up
+        // on reconnection from persisted ones
+        self.subscribers.lock().unwrap().retain(|subscriber| 
+            !matches!(subscriber.try_send(event.clone()), Err(TrySendError::Closed(_)))
+        );
+    }
+}
+
+pub struct Subscription {
+    username: String,
+    // authors followed when subscribing, whose new articles are delivered
+    following: HashSet<String>,
+    article_slug: Option<String>,
+    replay: VecDeque<Event>,
+    last_event_id: i64,
+    receiver: Receiver<Event>,
+}
+
+impl Subscription {
+    pub(crate) fn new(username: String, 
+        following: Vec<String>,
+        article_slug: Option<String>, 
+        replay: Vec<Event>,
+        last_event_id: i64,
+        receiver: Receiver<Event>
+    ) -> Self {
+        let last_event_id = replay.last()
+            .map(|event| event.id)
+            .unwrap_or(last_event_id);
+
+        Self {
+            username,
+            following: following.into_iter().collect(),
+            article_slug,
+            replay: replay.into(),
+            last_event_id,
+            receiver,
+        }
+    }
+
+    fn accepts(&self, event: &Event) -> bool {
+        // live events already replayed from database are skipped
+        event.id > self.last_event_id 
+        && (event.recipient.as_ref() == Some(&self.username)
+            || (event.kind == EventKind::Comment 
+                && self.article_slug.is_some() 
+                && event.article_slug == self.article_slug)
+            || (event.kind == EventKind::Article 
+                && event.author.as_ref().is_some_and(|author| self.following.contains(author))))
+    }
+
+    // waits for the next event, None if the bus has gone
+    pub async fn next(&mut self) -> Option<Event> {
+        if let Some(event) = self.replay.pop_front() {
+            return Some(event);
+        }
+        while let Ok(event) = self.receiver.recv().await {
+            if self.accepts(&event) {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    // next event if any is already available
+    pub fn try_next(&mut self) -> Option<Event> {
+        if let Some(event) = self.replay.pop_front() {
+            return Some(event);
+        }
+        while let Ok(event) = self.receiver.try_recv() {
+            if self.accepts(&event) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
@@ -1,7 +1,12 @@
 use tide::prelude::*;
 use crate::requests::{article::*};
 
-pub trait Filter: std::fmt::Display + Default {}
+pub trait Filter: std::fmt::Display + Default {
+    // joined to the filtered table, for filtering and ordering on computed columns
+    fn join(&self) -> String {
+        String::new()
+    }
+}
 
 pub(crate) struct UserFilter<'a> {
     pub username: Option<&'a str>,
@@ -40,6 +45,7 @@ impl std::fmt::Display for UserFilter<'_> {
 pub struct UpdateUserFilter<'a> {
     pub username: Option<&'a str>,
     pub email: Option<&'a str>,
+    pub version: Option<i32>,
 }
 
 impl<'a> UpdateUserFilter<'a> {
@@ -51,6 +57,10 @@ impl<'a> UpdateUserFilter<'a> {
         self.email = Some(email);
         self
     }
+    pub fn version(mut self, version: Option<i32>) -> Self {
+        self.version = version;
+        self
+    }
 }
 
 impl Filter for UpdateUserFilter<'_> {}
@@ -59,6 +69,7 @@ impl std::fmt::Display for UpdateUserFilter<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.username.as_ref().map(|val| write!( f, " {}='{}' AND", "users.username", val) ).unwrap_or(Ok(()))?;
         self.email.as_ref().map(|val| write!( f, " {}='{}' AND", "users.email", val) ).unwrap_or(Ok(()))?;
+        self.version.as_ref().map(|val| write!( f, " users.version={} AND", val) ).unwrap_or(Ok(()))?;
         write!( f, " 1=1")
     }
 }
@@ -67,6 +78,7 @@ impl Default for UpdateUserFilter<'_> {
         Self { 
             username: None,
             email: None,
+            version: None,
         }
     }
 }
@@ -116,41 +128,138 @@ impl std::fmt::Display for ArticleFilterByValues {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.author.as_ref().map(|val| write!( f, " {}='{}' AND", "author", val) ).unwrap_or(Ok(()))?;
         self.slug.as_ref().map(|val| write!( f, " {}='{}' AND", "slug", val) ).unwrap_or(Ok(()))?;
-        self.tag.as_ref().map(|val| write!( f, " {} LIKE '%{}%' AND", "tagList", val) ).unwrap_or(Ok(()))?;
+        self.tag.as_ref().map(|val| 
+            write!( f, " instr(lower(',' || tagList), ',{},') > 0 AND", val) 
+        ).unwrap_or(Ok(()))?;
         self.favorited.as_ref().map(|val| 
             write!( f, " {}='{}' AND", "favorite_articles.username", val) 
         ).unwrap_or(Ok(()))?;
+        // soft deleted articles are only reachable for restoring
+        write!( f, " articles.deletedAt IS NULL AND")?;
         write!( f, " 1=1")
     }
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedMode {
+    Authors,
+    Tags,
+    #[default]
+    All,
+}
+
 #[derive(Deserialize, Default)]
 #[serde(default)]
+pub struct FeedFilter {
+    pub mode: FeedMode,
+}
+
+impl FeedFilter {
+    pub fn mode(mut self, mode: FeedMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+#[derive(Default)]
 pub(crate) struct ArticleFilterFeed<'a> {
     pub follower: &'a str,
+    pub mode: FeedMode,
 }
 
 impl Filter for ArticleFilterFeed<'_> {}
 
 impl std::fmt::Display for ArticleFilterFeed<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!( f, 
+        let by_authors = format!(
             " {} IN (SELECT celeb_name FROM followers WHERE follower_name='{}')", 
-            "author", self.follower)
+            "author", self.follower);
+        // tagList is stored as comma terminated list, leading comma makes 
+        // every tag enclosed in commas
+        let by_tags = format!(
+            " EXISTS (SELECT 1 FROM followed_tags WHERE username='{}' AND \
+                instr(lower(',' || articles.tagList), ',' || lower(followed_tags.tag) || ',') > 0)", 
+            self.follower);
+
+        match self.mode {
+            FeedMode::Authors => write!( f, "{} AND", by_authors)?,
+            FeedMode::Tags => write!( f, "{} AND", by_tags)?,
+            FeedMode::All => write!( f, " ({} OR {}) AND", by_authors, by_tags)?,
+        }
+        write!( f, " articles.deletedAt IS NULL")
     }
 }
 
+// reading list is private, so it is only selected for its owner
+#[derive(Default)]
+pub(crate) struct ArticleFilterBookmarks<'a> {
+    pub username: &'a str,
+}
+
+impl Filter for ArticleFilterBookmarks<'_> {}
+
+impl std::fmt::Display for ArticleFilterBookmarks<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!( f, 
+            " articles.id IN (SELECT article_id FROM bookmarks WHERE username='{}') AND articles.deletedAt IS NULL", 
+            self.username)
+    }
+}
+
+// articles sharing tags, author or favoriting users with the given one,
+// ranked by score
+#[derive(Default)]
+pub(crate) struct ArticleFilterRelated {
+    pub article_id: i32,
+}
+
+impl ArticleFilterRelated {
+    pub const SCORE: &'static str = "related.score";
+}
+
+impl Filter for ArticleFilterRelated {
+    // score is computed once per candidate, every shared tag counts 3, 
+    // same author 2, every user having favorited both 1
+    fn join(&self) -> String {
+        format!("\
+            INNER JOIN (SELECT id, SUM(score) AS score FROM ( \
+                SELECT related_tags.article_id AS id, 3 AS score FROM article_tags AS source_tags \
+                    INNER JOIN article_tags AS related_tags ON related_tags.tag = source_tags.tag \
+                    WHERE source_tags.article_id = {id} \
+                UNION ALL \
+                SELECT same_author.id AS id, 2 AS score FROM articles AS source \
+                    INNER JOIN articles AS same_author ON same_author.author = source.author \
+                    WHERE source.id = {id} \
+                UNION ALL \
+                SELECT co_favorites.id AS id, 1 AS score FROM favorite_articles AS source_favorites \
+                    INNER JOIN favorite_articles AS co_favorites ON co_favorites.username = source_favorites.username \
+                    WHERE source_favorites.id = {id}) \
+                GROUP BY id) AS related ON related.id = articles.id", 
+            id = self.article_id)
+    }
+}
+
+impl std::fmt::Display for ArticleFilterRelated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!( f, 
+            " articles.id <> {} AND articles.deletedAt IS NULL", 
+            self.article_id)
+    }
+}
 
 #[derive(Default)]
 pub(crate) struct UpdateArticleFilter<'a> {
     pub slug: &'a str,
     pub author: &'a str,
+    pub version: Option<i32>,
 }
 impl Filter for UpdateArticleFilter<'_> {}
 
 impl std::fmt::Display for UpdateArticleFilter<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!( f, " slug='{}' AND author='{}'", self.slug, self.author)
+        self.version.as_ref().map(|val| write!( f, " version={} AND", val) ).unwrap_or(Ok(()))?;
+        write!( f, " slug='{}' AND author='{}' AND deletedAt IS NULL", self.slug, self.author)
     }
 }
 
@@ -159,6 +268,7 @@ impl<'a> From<&'a DeleteArticleRequestAuthenticated<'_>> for UpdateArticleFilter
         Self {
             slug: req.article_request.slug,
             author: &req.author,
+            version: None,
         }
     }
 }
@@ -168,12 +278,67 @@ impl<'a> From<&'a UpdateArticleRequestAuthenticated<'_>> for UpdateArticleFilter
         Self {
             slug: req.article_request.slug,
             author: &req.author,
+            version: req.article_request.if_match,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArticleSort {
+    #[default]
+    Recent,
+    // most favorited
+    Popular,
+    // by score of recent activity, see db::article::refresh_trending
+    Trending,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ArticleSortFilter {
+    pub sort: ArticleSort,
+}
+
+impl ArticleSortFilter {
+    pub fn sort(mut self, sort: ArticleSort) -> Self {
+        self.sort = sort;
+        self
+    }
+    pub fn order_by(&self) -> OrderByFilter<'static> {
+        match self.sort {
+            ArticleSort::Recent => OrderByFilter::Descending("updatedAt"),
+            ArticleSort::Popular => OrderByFilter::Descending("favoritesCount"),
+            ArticleSort::Trending => OrderByFilter::Descending(
+                "COALESCE((SELECT trendingScore FROM article_scores WHERE article_id = articles.id), 0)"),
         }
     }
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyFormat {
+    // raw text as written by the author
+    #[default]
+    Markdown,
+    // sanitized HTML in bodyHtml, in addition to raw text
+    Html,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct BodyFormatFilter {
+    pub format: BodyFormat,
+}
+
+impl BodyFormatFilter {
+    pub fn format(mut self, format: BodyFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
 pub enum OrderByFilter<'a> {
-    #[allow(dead_code)]
     Ascending(&'a str),
     Descending(&'a str),
     None,
@@ -216,6 +381,8 @@ impl<'a> CommentFilterByValues<'a> {
     }
 }
 
+impl Filter for CommentFilterByValues<'_> {}
+
 impl Default for CommentFilterByValues<'_> {
     fn default() -> Self {
         Self { 
@@ -245,20 +412,131 @@ impl<'a> From<&'a DeleteCommentRequestAuthenticated<'_>> for CommentFilterByValu
     }
 }
 
+impl<'a> From<&'a UpdateCommentRequestAuthenticated<'_>> for CommentFilterByValues<'a> {
+    fn from(req: &'a UpdateCommentRequestAuthenticated) -> Self {
+        Self {
+            id: Some(req.article_request.id),
+            article_slug: Some(req.article_request.article_slug),
+            author: Some(&req.author),
+        }
+    }
+}
+
 impl std::fmt::Display for CommentFilterByValues<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.id.as_ref().map(|val| write!( f, " {}='{}' AND", "id", val) ).unwrap_or(Ok(()))?;
         self.author.as_ref().map(|val| write!( f, " {}='{}' AND", "author", val) ).unwrap_or(Ok(()))?;
         self.article_slug.as_ref().map(|val|
             write!( f, 
-                " {} IN (SELECT id FROM articles WHERE slug='{}') AND", 
+                " {} IN (SELECT id FROM articles WHERE slug='{}' AND deletedAt IS NULL) AND", 
                 "article_id", val)
         ).unwrap_or(Ok(()))?;
+        // soft deleted comments having replies stay in threads as tombstones
+        write!( f, " (deletedAt IS NULL OR deleted=1) AND")?;
+
+        write!( f, " 1=1 ")
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentOrder {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+// selects a page of top level comments, cursor is the id of the last
+// top level comment on the previous page
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct CommentThreadFilter {
+    pub order: CommentOrder,
+    pub cursor: Option<i32>,
+}
+
+impl CommentThreadFilter {
+    pub fn order(mut self, order: CommentOrder) -> Self {
+        self.order = order;
+        self
+    }
+    pub fn cursor(mut self, cursor: i32) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+    pub fn order_by(&self) -> OrderByFilter<'static> {
+        match self.order {
+            CommentOrder::Newest => OrderByFilter::Descending("id"),
+            CommentOrder::Oldest => OrderByFilter::Ascending("id"),
+        }
+    }
+}
 
+impl Filter for CommentThreadFilter {}
+
+impl std::fmt::Display for CommentThreadFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!( f, " parentId IS NULL AND")?;
+        self.cursor.as_ref().map(|val| 
+            match self.order {
+                CommentOrder::Newest => write!( f, " id<{} AND", val),
+                CommentOrder::Oldest => write!( f, " id>{} AND", val),
+            }
+        ).unwrap_or(Ok(()))?;
         write!( f, " 1=1 ")
     }
 }
 
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct NotificationFilter {
+    #[serde(skip_deserializing)]
+    pub recipient: Option<String>,
+    #[serde(skip_deserializing)]
+    pub id: Option<i32>,
+    pub unread: Option<bool>,
+}
+
+impl NotificationFilter {
+    pub fn recipient(mut self, recipient: String) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+    pub fn id(mut self, id: i32) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn unread(mut self, unread: bool) -> Self {
+        self.unread = Some(unread);
+        self
+    }
+}
+
+impl Filter for NotificationFilter {}
+
+impl std::fmt::Display for NotificationFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.recipient.as_ref().map(|val| write!( f, " notifications.recipient='{}' AND", val) ).unwrap_or(Ok(()))?;
+        self.id.as_ref().map(|val| write!( f, " notifications.id={} AND", val) ).unwrap_or(Ok(()))?;
+        self.unread.as_ref().map(|val| write!( f, " notifications.read={} AND", !val as i32) ).unwrap_or(Ok(()))?;
+        write!( f, " 1=1")
+    }
+}
+
+// side length in pixels, the largest stored size by default
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AvatarFilter {
+    pub size: Option<u32>,
+}
+
+impl AvatarFilter {
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(default)]
 pub struct LimitOffsetFilter {
@@ -293,3 +571,197 @@ impl std::fmt::Display for LimitOffsetFilter {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct WebhookFilter {
+    pub owner: Option<String>,
+    pub id: Option<i32>,
+}
+
+impl WebhookFilter {
+    pub fn owner(mut self, owner: String) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+    pub fn id(mut self, id: i32) -> Self {
+        self.id = Some(id);
+        self
+    }
+}
+
+impl Filter for WebhookFilter {}
+
+impl std::fmt::Display for WebhookFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.owner.as_ref().map(|val| write!( f, " webhooks.owner='{}' AND", val) ).unwrap_or(Ok(()))?;
+        self.id.as_ref().map(|val| write!( f, " webhooks.id={} AND", val) ).unwrap_or(Ok(()))?;
+        write!( f, " 1=1")
+    }
+}
+
+// hides content of authors muted by viewer and, 
+// if hidden column is given, content hidden by moderators,
+// optionally content of authors blocked by or blocking viewer
+#[derive(Clone, Copy)]
+pub struct VisibilityFilter<'a> {
+    pub viewer: Option<&'a str>,
+    pub author_column: &'static str,
+    pub hidden_column: Option<&'static str>,
+    pub exclude_blocked: bool,
+}
+
+impl<'a> VisibilityFilter<'a> {
+    pub fn new(viewer: Option<&'a str>, author_column: &'static str) -> Self {
+        Self { viewer, author_column, hidden_column: None, exclude_blocked: false }
+    }
+    pub fn exclude_hidden(mut self, hidden_column: &'static str) -> Self {
+        self.hidden_column = Some(hidden_column);
+        self
+    }
+    pub fn exclude_blocked(mut self) -> Self {
+        self.exclude_blocked = true;
+        self
+    }
+}
+
+impl std::fmt::Display for VisibilityFilter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.viewer.as_ref().map(|val| 
+            write!( f, " {} NOT IN (SELECT muted_name FROM mutes WHERE muter_name='{}') AND", self.author_column, val) 
+        ).unwrap_or(Ok(()))?;
+        self.viewer.as_ref().filter(|_| self.exclude_blocked).map(|val| 
+            write!( f, 
+                " {} NOT IN (SELECT blocked_name FROM blocks WHERE blocker_name='{}') AND \
+                {} NOT IN (SELECT blocker_name FROM blocks WHERE blocked_name='{}') AND", 
+                self.author_column, val, self.author_column, val) 
+        ).unwrap_or(Ok(()))?;
+        self.hidden_column.as_ref().map(|val| write!( f, " {}=0 AND", val) ).unwrap_or(Ok(()))?;
+        write!( f, " 1=1")
+    }
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ReportFilter {
+    #[serde(skip_deserializing)]
+    pub id: Option<i32>,
+    pub status: Option<crate::models::report::ReportStatus>,
+}
+
+impl ReportFilter {
+    pub fn id(mut self, id: i32) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn status(mut self, status: crate::models::report::ReportStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+}
+
+impl Filter for ReportFilter {}
+
+impl std::fmt::Display for ReportFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.id.as_ref().map(|val| write!( f, " reports.id={} AND", val) ).unwrap_or(Ok(()))?;
+        self.status.as_ref().map(|val| write!( f, " reports.status='{}' AND", val.as_str()) ).unwrap_or(Ok(()))?;
+        write!( f, " 1=1")
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TagOrder {
+    #[default]
+    Popular,
+    Name,
+}
+
+// tags of visible articles, optionally only of articles published 
+// within the last `days`
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TagCloudFilter {
+    pub sort: TagOrder,
+    pub days: Option<u32>,
+}
+
+impl TagCloudFilter {
+    pub fn sort(mut self, sort: TagOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+    pub fn days(mut self, days: u32) -> Self {
+        self.days = Some(days);
+        self
+    }
+    pub fn order_by(&self) -> &'static str {
+        match self.sort {
+            TagOrder::Popular => "ORDER BY articlesCount DESC, name ASC ",
+            TagOrder::Name => "ORDER BY name ASC ",
+        }
+    }
+}
+
+impl Filter for TagCloudFilter {}
+
+impl std::fmt::Display for TagCloudFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.days.as_ref().map(|val| 
+            write!( f, " articles.createdAt >= datetime('now', '-{} days') AND", val) 
+        ).unwrap_or(Ok(()))?;
+        write!( f, " articles.deletedAt IS NULL AND articles.hidden=0")
+    }
+}
+
+// prefix typed by user for tag suggestions
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TagPrefixFilter {
+    pub prefix: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsBucketSize {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+// activity of the last `days` grouped into buckets
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct StatsFilter {
+    pub bucket: StatsBucketSize,
+    pub days: u32,
+}
+
+impl Default for StatsFilter {
+    fn default() -> Self {
+        Self {
+            bucket: StatsBucketSize::default(),
+            days: 30,
+        }
+    }
+}
+
+impl StatsFilter {
+    pub fn bucket(mut self, bucket: StatsBucketSize) -> Self {
+        self.bucket = bucket;
+        self
+    }
+    pub fn days(mut self, days: u32) -> Self {
+        self.days = days;
+        self
+    }
+    // first day of the bucket the time in given column falls into, 
+    // weeks start on Monday
+    pub fn bucket_start(&self, column: &str) -> String {
+        match self.bucket {
+            StatsBucketSize::Day => format!("date({})", column),
+            StatsBucketSize::Week => format!("date({}, '-6 days', 'weekday 1')", column),
+            StatsBucketSize::Month => format!("date({}, 'start of month')", column),
+        }
+    }
+}
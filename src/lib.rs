@@ -1,6 +1,8 @@
 mod db;
 mod auth;
 mod utils;
+mod markdown;
+mod avatar;
 mod endpoints;
 pub mod requests;
 pub mod filters;
@@ -9,4 +11,7 @@ pub mod errors;
 pub mod backend;
 pub mod app;
 pub mod config;
+pub mod events;
+pub mod webhooks;
+pub mod storage;
 
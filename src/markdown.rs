@@ -0,0 +1,124 @@
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+
+// CommonMark rendered to HTML keeping only allowlisted tags and attributes,
+// so raw HTML and javascript links in the source cannot reach the client
+pub(crate) fn render_html(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+
+    ammonia::clean(&unsafe_html)
+}
+
+const WORDS_PER_MINUTE: u32 = 200;
+const EXCERPT_LENGTH: usize = 200;
+
+// length of the text and its beginning with formatting stripped,
+// kept with the article so that listings need not parse its body
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TextStats {
+    pub(crate) word_count: u32,
+    pub(crate) reading_time_minutes: u32,
+    pub(crate) excerpt: String,
+}
+
+impl TextStats {
+    pub(crate) fn from_markdown(markdown: &str) -> Self {
+        let text = plain_text(markdown);
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        let word_count = words.len() as u32;
+
+        let mut excerpt = String::new();
+        for word in words.iter() {
+            if EXCERPT_LENGTH < excerpt.len() + word.len() {
+                // overlong first word, a link for instance, is cut instead
+                if excerpt.is_empty() {
+                    excerpt.extend(word.char_indices()
+                        .take_while(|(at, c)| at + c.len_utf8() <= EXCERPT_LENGTH)
+                        .map(|(_, c)| c));
+                }
+                excerpt.push('…');
+                break;
+            }
+            if !excerpt.is_empty() {
+                excerpt.push(' ');
+            }
+            excerpt.push_str(word);
+        }
+
+        Self {
+            word_count,
+            reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE),
+            excerpt,
+        }
+    }
+}
+
+// text and inline code only, raw HTML is dropped
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(fragment) | Event::Code(fragment) => text.push_str(&fragment),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::CodeBlock(_) 
+                | Tag::TableCell) => text.push(' '),
+            _ => (),
+        }
+    }
+    text
+}
+
+// bounds the work a single text can cause
+pub(crate) const MAX_MENTIONS: usize = 20;
+
+// usernames written as @username in text, code is not looked into,
+// each one is listed once in order of first appearance, up to MAX_MENTIONS
+pub(crate) fn mentions(markdown: &str) -> Vec<String> {
+    let mut usernames = Vec::<String>::new();
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            // parser may split text at characters that could start formatting
+            Event::Text(fragment) if !in_code_block => {
+                text.push_str(&fragment);
+                continue;
+            },
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            _ => (),
+        }
+        collect_mentions(&text, &mut usernames);
+        text.clear();
+    }
+    collect_mentions(&text, &mut usernames);
+    usernames
+}
+
+fn collect_mentions(text: &str, usernames: &mut Vec<String>) {
+    let is_username_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut previous = ' ';
+
+    for (position, c) in text.char_indices() {
+        if usernames.len() == MAX_MENTIONS {
+            return;
+        }
+        // e-mail addresses are not mentions
+        if c == '@' && !is_username_char(previous) {
+            let username = text[position + 1..]
+                .split(|c: char| !is_username_char(c))
+                .next()
+                .unwrap_or_default();
+            if !username.is_empty() && !usernames.iter().any(|known| known == username) {
+                usernames.push(username.to_string());
+            }
+        }
+        previous = c;
+    }
+}
@@ -8,6 +8,8 @@ use crate::requests::article::*;
 #[serde(rename_all = "camelCase")]
 #[sqlx(rename_all = "camelCase")]
 pub struct Article { 
+    #[serde(skip_serializing)]
+    pub id: i32,
     pub slug: String,
     pub title: String,
     pub description: Option<String>,
@@ -20,16 +22,33 @@ pub struct Article {
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
     pub favorited: bool,
     pub favorites_count: u32,
+    pub views_count: u32,
+    pub word_count: u32,
+    pub reading_time_minutes: u32,
     #[serde(skip_serializing)]
     pub author: String,
+    #[serde(skip_serializing)]
+    pub version: i32,
+    // hidden by moderator
+    #[serde(skip_serializing)]
+    pub hidden: bool,
+    // description is an excerpt of the body, replaced when the body changes
+    #[serde(skip_serializing)]
+    pub generated_description: bool,
 }
 
 impl From<CreateArticleRequestAuthenticated> for Article {
     fn from(create_article: CreateArticleRequestAuthenticated) -> Self {
+        let text_stats = crate::markdown::TextStats::from_markdown(&create_article.article_request.body);
+        let description = create_article.article_request.description.clone()
+            .filter(|description| !description.trim().is_empty());
+        let generated_description = description.is_none();
+
         Self { 
+            id: 0,
             slug: create_article.article_request.slug.clone(), 
             title: create_article.article_request.title.clone(),
-            description: create_article.article_request.description.clone(),
+            description: description.or(Some(text_stats.excerpt)),
             body: create_article.article_request.body.clone(),
             tag_list: create_article.article_request.tag_list.as_ref()
                 .and_then(|tags| 
@@ -41,7 +60,13 @@ impl From<CreateArticleRequestAuthenticated> for Article {
             updated_at: None,
             favorited: false,
             favorites_count: 0,
+            views_count: 0,
+            word_count: text_stats.word_count,
+            reading_time_minutes: text_stats.reading_time_minutes,
             author: create_article.author.to_string(),
+            version: 1,
+            hidden: false,
+            generated_description,
         }
     }
 }
@@ -52,6 +77,12 @@ pub struct UpdateArticle {
     pub title: Option<String>,
     pub description: Option<String>,
     pub body: Option<String>,
+    // unique slug resolved by database layer on title change
+    #[serde(skip_deserializing)]
+    pub slug: Option<String>,
+    // computed by database layer on body change
+    #[serde(skip_deserializing)]
+    pub(crate) text_stats: Option<crate::markdown::TextStats>,
 }
 
 impl UpdateArticle {
@@ -70,14 +101,38 @@ impl UpdateArticle {
     pub fn get_slug(&self) -> Option<String> {
         self.title.as_ref().and_then(|title| Some(slugify!(title)))
     }
+    pub(crate) fn get_text_stats(&self) -> Option<crate::markdown::TextStats> {
+        self.body.as_ref().map(|body| crate::markdown::TextStats::from_markdown(body))
+    }
+    // empty description is replaced by excerpt of the body, as on create
+    pub(crate) fn clears_description(&self) -> bool {
+        self.description.as_ref().is_some_and(|description| description.trim().is_empty())
+    }
 }
 
 impl std::fmt::Display for UpdateArticle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.title.as_ref().map(|val| 
-            write!( f, " {}='{}', {}='{}', ", "title", val, "slug", slugify!(val)) ).unwrap_or(Ok(()))?;
-        self.description.as_ref().map(|val| write!( f, " {}='{}', ", "description", val) ).unwrap_or(Ok(()))?;
+        self.title.as_ref().map(|val| write!( f, " title='{}', ", val) ).unwrap_or(Ok(()))?;
+        self.slug.as_ref().map(|val| write!( f, " slug='{}', ", val) ).unwrap_or(Ok(()))?;
+        if !self.clears_description() {
+            self.description.as_ref().map(|val| write!( f, " {}='{}', ", "description", val) ).unwrap_or(Ok(()))?;
+        }
         self.body.as_ref().map(|val| write!( f, " {}='{}', ", "body", val) ).unwrap_or(Ok(()))?;
+        if let Some(text_stats) = self.text_stats.as_ref() {
+            write!( f, " wordCount={}, readingTimeMinutes={}, ", 
+                text_stats.word_count, text_stats.reading_time_minutes)?;
+            // excerpt of previous body is replaced unless the author writes a description
+            if self.clears_description() {
+                write!( f, " description='{}', generatedDescription=1, ", 
+                    text_stats.excerpt.replace('\'', "''"))?;
+            } else if self.description.is_none() {
+                write!( f, " description=CASE WHEN generatedDescription THEN '{}' ELSE description END, ", 
+                    text_stats.excerpt.replace('\'', "''"))?;
+            }
+        }
+        if self.description.is_some() && !self.clears_description() {
+            write!( f, " generatedDescription=0, ")?;
+        }
         write!( f, " id=id ")
     }
 }
@@ -88,6 +143,8 @@ impl Default for UpdateArticle {
             title: None,
             description: None,
             body: None,
+            slug: None,
+            text_stats: None,
         }
     }
 }
@@ -97,6 +154,14 @@ pub struct ArticleResponse {
     pub author: Option<super::user::Profile>,
     #[serde(flatten)]
     pub article: Article,
+    pub reactions: Vec<super::reaction::ReactionCount>,
+    // in reading list of the viewer, never shown to others
+    pub bookmarked: bool,
+    // usernames of existing users mentioned in the body
+    pub mentions: Vec<String>,
+    // rendered body, only when requested
+    #[serde(rename = "bodyHtml", skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
 }
 
 impl ArticleResponse {
@@ -141,6 +206,14 @@ pub struct Comment {
     pub body: String,
     #[serde(skip_serializing)]
     pub author: String,
+    pub parent_id: Option<i32>,
+    pub depth: i32,
+    // deleted comment having replies is kept without its text
+    pub deleted: bool,
+    pub edited: bool,
+    // hidden by moderator
+    #[serde(skip_serializing)]
+    pub hidden: bool,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -148,6 +221,12 @@ pub struct CommentResponse {
     pub author: Option<crate::models::user::Profile>,
     #[serde(flatten)]
     pub comment: Comment,
+    pub reactions: Vec<crate::models::reaction::ReactionCount>,
+    // usernames of existing users mentioned in the body
+    pub mentions: Vec<String>,
+    // rendered body, only when requested
+    #[serde(rename = "bodyHtml", skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
 }
 
 impl CommentResponse {
@@ -165,12 +244,45 @@ pub(crate) struct CommentResponseWrapped {
 #[serde(rename_all = "camelCase")]
 pub struct MultipleCommentResponse {
     pub comments: Vec<CommentResponse>,
+    // total number of article comments, not only the ones on the page
+    pub comments_count: i64,
 }
 
 impl MultipleCommentResponse {
     pub fn from_comments(comments: Vec<CommentResponse>) -> Self {
+        let comments_count = comments.len() as i64;
         Self { 
             comments,
+            comments_count,
+        }
+    }
+
+    // orders comments so that every comment is followed by its replies,
+    // siblings keep their original order, comments whose parent is 
+    // not among the given ones are treated as top level
+    pub fn from_comments_threaded(comments: Vec<CommentResponse>, comments_count: i64) -> Self {
+        let ids = comments.iter()
+            .map(|comment| comment.comment.id)
+            .collect::<std::collections::HashSet<i32>>();
+
+        let mut replies = std::collections::HashMap::<Option<i32>, Vec<CommentResponse>>::new();
+        for comment in comments.into_iter().rev() {
+            let parent_id = comment.comment.parent_id.filter(|id| ids.contains(id));
+            replies.entry(parent_id).or_default().push(comment);
+        }
+        // replies are stacked in reversed order, so popping them restores it
+        let mut stack = replies.remove(&None).unwrap_or_default();
+        let mut threaded = Vec::with_capacity(ids.len());
+        
+        while let Some(comment) = stack.pop() {
+            if let Some(comment_replies) = replies.remove(&Some(comment.comment.id)) {
+                stack.extend(comment_replies);
+            }
+            threaded.push(comment);
+        }
+        Self { 
+            comments: threaded,
+            comments_count,
         }
     }
 }
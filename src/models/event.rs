@@ -0,0 +1,34 @@
+#[derive(sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+pub enum EventKind {
+    Article,
+    Comment,
+    Notification,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Article => "article",
+            Self::Comment => "comment",
+            Self::Notification => "notification",
+        }
+    }
+}
+
+// event delivered to its recipient, comment events are delivered
+// also to users viewing the commented article, article events
+// have no recipient and go to followers of their author
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Clone)]
+#[sqlx(rename_all = "camelCase")]
+pub struct Event {
+    pub id: i64,
+    pub kind: EventKind,
+    pub recipient: Option<String>,
+    pub author: Option<String>,
+    pub article_slug: Option<String>,
+    // json serialized event data
+    pub payload: String,
+}
@@ -1,2 +1,10 @@
 pub mod user;
 pub mod article;
+pub mod reaction;
+pub mod notification;
+pub mod event;
+pub mod webhook;
+pub mod report;
+pub mod tag;
+pub mod stats;
+pub mod upload;
@@ -0,0 +1,101 @@
+use tide::prelude::*;
+use crate::utils::*;
+
+#[derive(sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum NotificationKind {
+    Follow,
+    Favorite,
+    Comment,
+    // moderator warning about reported content
+    Warning,
+    // user mentioned in article or comment
+    Mention,
+}
+
+impl NotificationKind {
+    // moderator warnings are always delivered
+    pub fn can_be_muted(&self) -> bool {
+        *self != Self::Warning
+    }
+}
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: i32,
+    pub kind: NotificationKind,
+    #[serde(skip_serializing)]
+    pub recipient: String,
+    #[serde(skip_serializing)]
+    pub actor: String,
+    pub article_slug: Option<String>,
+    pub comment_id: Option<i32>,
+    pub read: bool,
+    #[serde(serialize_with = "transform_datetime")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// notification about to be created, it is dropped if
+// recipient has muted notifications of this kind
+pub(crate) struct NewNotification<'a> {
+    pub kind: NotificationKind,
+    pub recipient: &'a str,
+    pub actor: &'a str,
+    pub article_slug: Option<&'a str>,
+    pub comment_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NotificationResponse {
+    pub actor: Option<super::user::Profile>,
+    #[serde(flatten)]
+    pub notification: Notification,
+}
+
+impl NotificationResponse {
+    pub(crate) fn wrap(self) -> NotificationResponseWrapped {
+        NotificationResponseWrapped { notification: self }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct NotificationResponseWrapped {
+    pub notification: NotificationResponse,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleNotificationResponse {
+    pub notifications: Vec<NotificationResponse>,
+    // total number of notifications matching the filter
+    pub notifications_count: i64,
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadNotificationsCount {
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub muted: Vec<NotificationKind>,
+}
+
+impl NotificationPreferences {
+    pub(crate) fn wrap(self) -> NotificationPreferencesWrapped {
+        NotificationPreferencesWrapped { preferences: self }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct NotificationPreferencesWrapped {
+    pub preferences: NotificationPreferences,
+}
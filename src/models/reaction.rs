@@ -0,0 +1,65 @@
+use tide::prelude::*;
+use crate::errors::BackendError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionKind {
+    ThumbsUp,
+    Heart,
+    Tada,
+    Thinking,
+}
+
+impl ReactionKind {
+    pub const ALL: [ReactionKind; 4] = [
+        Self::ThumbsUp,
+        Self::Heart,
+        Self::Tada,
+        Self::Thinking,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ThumbsUp => "thumbsup",
+            Self::Heart => "heart",
+            Self::Tada => "tada",
+            Self::Thinking => "thinking",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Self::ThumbsUp => "👍",
+            Self::Heart => "❤️",
+            Self::Tada => "🎉",
+            Self::Thinking => "🤔",
+        }
+    }
+}
+
+impl std::str::FromStr for ReactionKind {
+    type Err = BackendError;
+    // accepts both reaction name and its emoji
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter()
+            .find(|kind| kind.as_str() == s || kind.emoji() == s)
+            .copied()
+            .ok_or_else(|| BackendError::ValidationError(format!("unknown reaction {}", s)))
+    }
+}
+
+impl std::fmt::Display for ReactionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!( f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionCount {
+    pub kind: ReactionKind,
+    pub emoji: &'static str,
+    pub count: i64,
+    // true if the authenticated viewer has reacted with this kind
+    pub reacted: bool,
+}
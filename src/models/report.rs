@@ -0,0 +1,74 @@
+use tide::prelude::*;
+use crate::utils::*;
+
+#[derive(sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum ReportStatus {
+    Open,
+    Dismissed,
+    Actioned,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Dismissed => "dismissed",
+            Self::Actioned => "actioned",
+        }
+    }
+}
+
+#[derive(sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum ModerationAction {
+    // reported article or comment is no longer shown
+    Hide,
+    // author of reported content is notified
+    Warn,
+    // author of reported content cannot publish anymore
+    Suspend,
+}
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct Report {
+    pub id: i32,
+    pub reporter: String,
+    pub article_slug: String,
+    pub comment_id: Option<i32>,
+    // author of reported article or comment
+    pub author: String,
+    pub reason: String,
+    pub status: ReportStatus,
+    pub action: Option<ModerationAction>,
+    pub moderator: Option<String>,
+    #[serde(serialize_with = "transform_datetime")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(serialize_with = "transform_datetime_option")]
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Report {
+    pub(crate) fn wrap(self) -> ReportWrapped {
+        ReportWrapped { report: self }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ReportWrapped {
+    pub report: Report,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleReportResponse {
+    pub reports: Vec<Report>,
+    pub reports_count: i64,
+}
@@ -0,0 +1,41 @@
+use tide::prelude::*;
+
+// activity on an article within one time bucket, start being its first day
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsBucket {
+    pub start: String,
+    pub views: i64,
+    pub favorites: i64,
+    pub comments: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleStats {
+    #[serde(skip_serializing)]
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+    // totals since the article has been published
+    pub views_count: i64,
+    pub favorites_count: i64,
+    pub comments_count: i64,
+    pub buckets: Vec<StatsBucket>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AuthorStats {
+    pub articles: Vec<ArticleStats>,
+}
+
+impl AuthorStats {
+    pub(crate) fn wrap(self) -> AuthorStatsWrapped {
+        AuthorStatsWrapped { stats: self }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AuthorStatsWrapped {
+    pub stats: AuthorStats,
+}
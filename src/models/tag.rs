@@ -0,0 +1,42 @@
+use tide::prelude::*;
+
+// tags are compared case insensitively, so they are stored lowercased
+pub(crate) fn normalize(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct TagCount {
+    pub name: String,
+    pub articles_count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCloud {
+    pub tags: Vec<TagCount>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub name: String,
+    pub description: Option<String>,
+    // names mapped to this tag when articles are written or queried
+    pub aliases: Vec<String>,
+    pub articles_count: i64,
+}
+
+impl Tag {
+    pub(crate) fn wrap(self) -> TagWrapped {
+        TagWrapped { tag: self }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TagWrapped {
+    pub tag: Tag,
+}
@@ -0,0 +1,50 @@
+use tide::prelude::*;
+use crate::utils::*;
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct Upload {
+    // hex encoded SHA-256 of the content
+    pub id: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde(serialize_with = "transform_datetime")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub(crate) fn upload_url(id: &str) -> String {
+    format!("/uploads/{}", id)
+}
+
+impl Upload {
+    // path the file is served at, to be used in profile image or markdown
+    pub fn url(&self) -> String {
+        upload_url(&self.id)
+    }
+
+    pub(crate) fn wrap(self) -> UploadWrapped {
+        UploadWrapped { upload: UploadResponse { url: self.url(), upload: self } }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct UploadResponse {
+    #[serde(flatten)]
+    pub upload: Upload,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct UploadWrapped {
+    pub upload: UploadResponse,
+}
+
+// processed avatar or profile image to redirect to,
+// otherwise identicon generated on request
+#[derive(Debug)]
+pub enum Avatar {
+    Url(String),
+    Generated(Vec<u8>),
+}
@@ -18,6 +18,7 @@ pub(crate) struct User {
     pub image: Option<String>, 
 //    #[serde(skip_serializing)]
     pub(crate) hashed_password: String,
+    pub version: i32,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -28,6 +29,8 @@ pub struct LoggedInUser {
     pub username: String,    
     pub bio: String,    
     pub image: Option<String>, 
+    #[serde(skip_serializing)]
+    pub version: i32,
 }
 
 impl LoggedInUser {
@@ -38,6 +41,7 @@ impl LoggedInUser {
             username: user.username,
             bio: user.bio,
             image: user.image,
+            version: user.version,
             token
         }
     }
@@ -64,6 +68,7 @@ impl TryFrom<requests::user::UserReg> for User {
 //            token: None,
             bio: "".to_string(), 
             image: None,
+            version: 1,
         })
     }
 }
@@ -83,6 +88,7 @@ impl User {
             username: self.username,    
             bio: self.bio,    
             image: self.image,      
+            version: self.version,
         }) 
     }
 }
@@ -94,11 +100,16 @@ pub(crate) struct UserWrapped {
 
 #[derive(Debug, Serialize, Clone)]
 #[derive(sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
 pub struct Profile {
     pub username: String,    
     pub bio: Option<String>,    
     pub image: Option<String>,  
     pub following: bool,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub articles_count: i64,
 }
 
 impl Profile {
@@ -112,6 +123,13 @@ pub(crate) struct ProfileWrapped {
     pub profile: Profile,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleProfileResponse {
+    pub profiles: Vec<Profile>,
+    pub profiles_count: i64,
+}
+
 #[derive(Debug)]
 pub(crate) struct UserUpdate<'a> {
     pub username: Option<&'a str>,
@@ -0,0 +1,126 @@
+use tide::prelude::*;
+use crate::utils::*;
+
+#[derive(sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    #[serde(rename = "article.created")]
+    #[sqlx(rename = "article.created")]
+    ArticleCreated,
+    #[serde(rename = "article.updated")]
+    #[sqlx(rename = "article.updated")]
+    ArticleUpdated,
+    #[serde(rename = "article.deleted")]
+    #[sqlx(rename = "article.deleted")]
+    ArticleDeleted,
+    #[serde(rename = "comment.created")]
+    #[sqlx(rename = "comment.created")]
+    CommentCreated,
+    #[serde(rename = "user.followed")]
+    #[sqlx(rename = "user.followed")]
+    UserFollowed,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ArticleCreated => "article.created",
+            Self::ArticleUpdated => "article.updated",
+            Self::ArticleDeleted => "article.deleted",
+            Self::CommentCreated => "comment.created",
+            Self::UserFollowed => "user.followed",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub owner: String,
+    pub url: String,
+    // shared secret payloads are signed with, never returned back
+    #[serde(skip_serializing)]
+    pub secret: String,
+    #[serde(serialize_with = "transform_datetime")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookResponse {
+    pub(crate) fn wrap(self) -> WebhookResponseWrapped {
+        WebhookResponseWrapped { webhook: self }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct WebhookResponseWrapped {
+    pub webhook: WebhookResponse,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MultipleWebhookResponse {
+    pub webhooks: Vec<WebhookResponse>,
+}
+
+#[derive(sqlx::Type)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    // gave up after exhausting all attempts
+    Failed,
+}
+
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    // http status of the last attempt, None if receiver was not reached
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    #[serde(serialize_with = "transform_datetime")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(serialize_with = "transform_datetime_option")]
+    pub next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(serialize_with = "transform_datetime_option")]
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleWebhookDeliveryResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+    pub deliveries_count: i64,
+}
+
+// delivery due for an attempt along with its destination
+#[derive(sqlx::FromRow)]
+#[derive(Debug, Clone)]
+#[sqlx(rename_all = "camelCase")]
+pub(crate) struct PendingDelivery {
+    pub id: i32,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
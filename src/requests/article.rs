@@ -56,13 +56,18 @@ pub(crate) struct UpdateArticleRequestBody {
 pub struct UpdateArticleRequest<'a> {
     pub article: UpdateArticle,
     pub slug: &'a str,
+    // article version the update is based on, taken from If-Match header
+    pub if_match: Option<i32>,
 }
 
 impl <'a> UpdateArticleRequest<'a> {
-    pub(crate) fn from_req_parts(body: UpdateArticleRequestBody, slug: &'a str) -> Self {
+    pub(crate) fn from_req_parts(body: UpdateArticleRequestBody, 
+        slug: &'a str, 
+        if_match: Option<i32>) -> Self {
         Self {
             article: body.article,
             slug,
+            if_match,
         }
     }
 }
@@ -113,6 +118,7 @@ impl<'a> AuthenticatedRequest for DeleteArticleRequestAuthenticated<'a> {
 #[serde(rename_all = "camelCase")] 
 struct AddCommentRequestBody { 
     pub body: String,
+    pub parent_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -123,6 +129,8 @@ pub(crate) struct AddCommentRequestBodyWrapped {
 pub struct AddCommentRequest<'a> {
     pub body: String,
     pub article_slug: &'a str,
+    // comment being replied to
+    pub parent_id: Option<i32>,
 }
 
 impl<'a> AddCommentRequest<'a> {
@@ -131,7 +139,8 @@ impl<'a> AddCommentRequest<'a> {
         article_slug: &'a str) -> Self {
             Self { 
                 body: body_wrapped.comment.body,
-                article_slug
+                article_slug,
+                parent_id: body_wrapped.comment.parent_id,
             }
     }
 }
@@ -177,3 +186,69 @@ impl<'a> AuthenticatedRequest for DeleteCommentRequestAuthenticated<'a> {
     }
 }
 
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")] 
+struct UpdateCommentRequestBody { 
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct UpdateCommentRequestBodyWrapped { 
+    comment: UpdateCommentRequestBody,
+}
+
+pub struct UpdateCommentRequest<'a> {
+    pub id: i32,
+    pub body: String,
+    pub article_slug: &'a str,
+}
+
+impl<'a> UpdateCommentRequest<'a> {
+    pub(crate) fn from_req_parts(
+        body_wrapped: UpdateCommentRequestBodyWrapped, 
+        id: i32,
+        article_slug: &'a str) -> Self {
+            Self { 
+                id,
+                body: body_wrapped.comment.body,
+                article_slug,
+            }
+    }
+}
+
+impl<'a> IntoAuthenticatedRequest<UpdateCommentRequestAuthenticated<'a>> for UpdateCommentRequest<'a> {
+}
+
+pub(crate) struct UpdateCommentRequestAuthenticated<'a> {
+    pub article_request: UpdateCommentRequest<'a>,
+    pub author: String,
+}
+
+impl<'a> AuthenticatedRequest for UpdateCommentRequestAuthenticated<'a> {
+    type FromRequest = UpdateCommentRequest<'a>;
+    fn from_request_with_claims(req: Self::FromRequest, claims: crate::auth::Claims) -> Self {
+        Self {
+            article_request: req,
+            author: claims.username,
+        }
+    }
+}
+
+// user agent fragments of crawlers and link preview fetchers
+const CRAWLER_AGENTS: [&str; 8] = ["bot", "crawl", "spider", "slurp", "preview", "headless", "curl", "wget"];
+
+// client an article is viewed by, anonymous viewers are told apart by address
+#[derive(Debug, Default, Clone)]
+pub struct ViewContext {
+    pub remote: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ViewContext {
+    pub fn is_crawler(&self) -> bool {
+        self.user_agent.as_ref().is_some_and(|user_agent| {
+            let user_agent = user_agent.to_lowercase();
+            CRAWLER_AGENTS.iter().any(|agent| user_agent.contains(agent))
+        })
+    }
+}
@@ -0,0 +1,9 @@
+use tide::prelude::*;
+
+// browser EventSource cannot set headers, so token may be passed in query
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct EventStreamRequest {
+    pub token: Option<String>,
+    // slug of article being viewed, for receiving its comment events
+    pub article: Option<String>,
+}
@@ -1,6 +1,11 @@
 
 pub mod user;
 pub mod article;
+pub(crate) mod event;
+pub mod webhook;
+pub mod report;
+pub mod tag;
+pub mod upload;
 
 use crate::auth::{Auth, Claims};
 use crate::errors::BackendError;
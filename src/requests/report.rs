@@ -0,0 +1,24 @@
+use tide::prelude::*;
+use validator::{Validate};
+use crate::models::report::ModerationAction;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReportRequest {
+    #[validate(length(min = 1, max = 1000, message = "report reason must be 1 to 1000 characters long"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReportRequestWrapped {
+    pub report: ReportRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationRequest {
+    pub action: ModerationAction,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ModerationRequestWrapped {
+    pub moderation: ModerationRequest,
+}
@@ -0,0 +1,33 @@
+use tide::prelude::*;
+use validator::{Validate};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateTagRequest {
+    #[validate(length(max = 500, message = "tag description must be at most 500 characters long"))]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UpdateTagRequestWrapped {
+    pub tag: UpdateTagRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagAliasRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TagAliasRequestWrapped {
+    pub alias: TagAliasRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeTagRequest {
+    pub into: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MergeTagRequestWrapped {
+    pub merge: MergeTagRequest,
+}
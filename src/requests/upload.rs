@@ -0,0 +1,24 @@
+// file types accepted for upload with their leading bytes,
+// declared content type of the client is not trusted
+const SIGNATURES: [(&[u8], &str); 5] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF8", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"RIFF", "image/webp"),
+];
+
+#[derive(Debug)]
+pub struct UploadRequest {
+    pub data: Vec<u8>,
+}
+
+impl UploadRequest {
+    pub fn content_type(&self) -> Option<&'static str> {
+        SIGNATURES.iter()
+            .find(|(signature, content_type)| self.data.starts_with(signature) 
+                // RIFF container holds other formats as well
+                && (*content_type != "image/webp" || self.data.get(8..12) == Some(b"WEBP")))
+            .map(|(_, content_type)| *content_type)
+    }
+}
@@ -22,6 +22,9 @@ pub struct UserUpdateRequest {
     pub password: Option<String>,
     pub bio: Option<String>,
     pub image: Option<String>,
+    // user version the update is based on, taken from If-Match header
+    #[serde(skip_deserializing)]
+    pub if_match: Option<i32>,
 }
 
 impl Default for UserUpdateRequest {
@@ -32,6 +35,7 @@ impl Default for UserUpdateRequest {
             password: None,
             bio: None,
             image: None,
+            if_match: None,
         }
     }
 }
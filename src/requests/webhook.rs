@@ -0,0 +1,18 @@
+use tide::prelude::*;
+use validator::{Validate};
+use crate::models::webhook::WebhookEvent;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+    #[validate(url)]
+    pub url: String,
+    #[validate(length(min = 16, message = "webhook secret must be at least 16 characters long"))]
+    pub secret: String,
+    #[validate(length(min = 1, message = "webhook must subscribe at least one event"))]
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateWebhookRequestWrapped {
+    pub webhook: CreateWebhookRequest,
+}
@@ -0,0 +1,64 @@
+// backends keeping uploaded files, addressed by keys chosen by the server
+use crate::errors::BackendError;
+
+#[async_trait::async_trait]
+pub trait Storage: std::fmt::Debug + Send + Sync {
+    // stores data under the key, replacing what was there
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), BackendError>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, BackendError>;
+    // removing missing key is not an error
+    async fn delete(&self, key: &str) -> Result<(), BackendError>;
+}
+
+// files in a directory of local file system, created on first upload
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: std::path::PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.root.join(key)
+    }
+}
+
+// io errors are server failures here, not connection ones
+struct StorageError(std::io::Error);
+
+impl From<StorageError> for BackendError {
+    fn from(err: StorageError) -> Self {
+        BackendError::UnexpectedError(format!("storage failure: {}", err.0))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), BackendError> {
+        async_std::fs::create_dir_all(&self.root).await.map_err(StorageError)?;
+        // written aside and renamed, so that readers never see a partial file
+        let partial = self.path(&format!("{}.part", key));
+        async_std::fs::write(&partial, data).await.map_err(StorageError)?;
+        async_std::fs::rename(&partial, self.path(key)).await.map_err(StorageError)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, BackendError> {
+        match async_std::fs::read(self.path(key)).await {
+            Ok(data) => Ok(data),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 
+                Err(BackendError::NoUploadFound(key.to_string())),
+            Err(err) => Err(StorageError(err).into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BackendError> {
+        match async_std::fs::remove_file(self.path(key)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(StorageError(err).into()),
+            _ => Ok(()),
+        }
+    }
+}
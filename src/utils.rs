@@ -43,3 +43,94 @@ pub(crate) fn token_from_request(req: &Request) -> Result<&str, tide::Error> {
     let token = hdr.as_str().trim_start_matches(TOKEN).trim_start();
     Ok(token)
 }
+
+// token for endpoints available to anonymous users as well
+pub(crate) fn optional_token_from_request(req: &Request) -> Option<&str> {
+    token_from_request(req).ok()
+}
+
+// id of the last event received by reconnecting event stream client
+pub(crate) fn last_event_id_from_request(req: &Request) -> Option<i64> {
+    req.header("Last-Event-ID")
+        .and_then(|hdr| hdr.get(0))
+        .and_then(|hdr| hdr.as_str().trim().parse::<i64>().ok())
+}
+
+// address without port and user agent of the client, address is taken from
+// X-Forwarded-For only behind trusted proxies, the rightmost address not 
+// belonging to a trusted proxy is the client, as the ones left of it 
+// might have been supplied by the client itself, the peer is kept 
+// when every forwarded address belongs to a trusted proxy
+pub(crate) fn view_context_from_request(req: &Request) -> crate::requests::article::ViewContext {
+    let ip = |addr: &str| addr.parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| addr.to_string());
+    let server = &req.state().server;
+
+    let mut remote = req.peer_addr().map(ip);
+    if remote.as_deref().is_some_and(|peer| server.trusts_proxy(peer)) {
+        let forwarded = req.header("X-Forwarded-For")
+            .map(|hdr| hdr.iter()
+                .flat_map(|value| value.as_str().split(','))
+                .map(|addr| addr.trim().to_string())
+                .filter(|addr| !addr.is_empty())
+                .collect::<Vec<String>>())
+            .unwrap_or_default();
+        if let Some(client) = forwarded.iter().rev().find(|addr| !server.trusts_proxy(addr)) {
+            remote = Some(client.clone());
+        }
+    }
+    let user_agent = req.header(http_types::headers::USER_AGENT)
+        .and_then(|hdr| hdr.get(0))
+        .map(|hdr| hdr.as_str().to_string());
+
+    crate::requests::article::ViewContext { remote, user_agent }
+}
+
+pub(crate) fn with_etag(version: i32, res: impl Into<tide::Response>) -> tide::Response {
+    let mut res = res.into();
+    res.insert_header(http_types::headers::ETAG, format!("\"{}\"", version));
+    res
+}
+
+// version an update is based on, None if request is unconditional
+pub(crate) fn if_match_from_request(req: &Request) -> Result<Option<i32>, tide::Error> {
+    let hdr = match req.header(http_types::headers::IF_MATCH).and_then(|hdr| hdr.get(0)) {
+        Some(hdr) if hdr.as_str().trim() != "*" => hdr.as_str().trim(),
+        _ => return Ok(None),
+    };
+
+    hdr.trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<i32>()
+        .map(Some)
+        // malformed entity tag never matches current version
+        .map_err(|_| tide::Error::from_str(tide::StatusCode::PreconditionFailed, "malformed If-Match header"))
+}
+
+// content of "file" field of multipart/form-data body, 
+// body must declare its length within upload limit
+pub(crate) async fn file_from_multipart(req: &mut Request) -> Result<Vec<u8>, tide::Error> {
+    let limit = req.state().server.upload_size_limit();
+    req.len()
+        .filter(|len| *len as u64 <= limit)
+        .ok_or(tide::Error::from_str(tide::StatusCode::PayloadTooLarge, 
+            format!("upload must declare its length of at most {} bytes", limit)))?;
+    let boundary = req.content_type()
+        .and_then(|mime| mime.param("boundary").map(|boundary| boundary.to_string()))
+        .ok_or(tide::Error::from_str(tide::StatusCode::BadRequest, "multipart/form-data body expected"))?;
+
+    let body = req.body_bytes().await?;
+    let mut multipart = multer::Multipart::new(
+        async_std::stream::once(Ok::<_, std::io::Error>(body)), boundary);
+
+    while let Some(field) = multipart.next_field().await
+        .map_err(|err| tide::Error::from_str(tide::StatusCode::BadRequest, err.to_string()))? {
+        if field.name() == Some("file") {
+            return field.bytes().await
+                .map(|data| data.to_vec())
+                .map_err(|err| tide::Error::from_str(tide::StatusCode::BadRequest, err.to_string()));
+        }
+    }
+    Err(tide::Error::from_str(tide::StatusCode::BadRequest, "no file field in upload"))
+}
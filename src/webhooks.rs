@@ -0,0 +1,146 @@
+// signing and delivery of webhook payloads, receivers verify
+// the signature header against HMAC-SHA256 of the raw request body
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use async_std::net::{TcpStream, ToSocketAddrs};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use http_types::{Request, Response, Url};
+
+use crate::models::webhook::PendingDelivery;
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// signature of payload in form of "sha256=<hex digest>"
+pub fn signature(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// delay before next attempt, doubled after every failed one
+pub(crate) fn backoff_seconds(base_seconds: i64, attempts: i32) -> i64 {
+    base_seconds.saturating_mul(1 << attempts.clamp(0, 30))
+}
+
+// special purpose IPv4 ranges, none of them is reachable across the internet
+const NON_GLOBAL_V4: [(Ipv4Addr, u32); 15] = [
+    (Ipv4Addr::new(0, 0, 0, 0), 8),
+    (Ipv4Addr::new(10, 0, 0, 0), 8),
+    (Ipv4Addr::new(100, 64, 0, 0), 10),
+    (Ipv4Addr::new(127, 0, 0, 0), 8),
+    (Ipv4Addr::new(169, 254, 0, 0), 16),
+    (Ipv4Addr::new(172, 16, 0, 0), 12),
+    (Ipv4Addr::new(192, 0, 0, 0), 24),
+    (Ipv4Addr::new(192, 0, 2, 0), 24),
+    (Ipv4Addr::new(192, 88, 99, 0), 24),
+    (Ipv4Addr::new(192, 168, 0, 0), 16),
+    (Ipv4Addr::new(198, 18, 0, 0), 15),
+    (Ipv4Addr::new(198, 51, 100, 0), 24),
+    (Ipv4Addr::new(203, 0, 113, 0), 24),
+    (Ipv4Addr::new(224, 0, 0, 0), 4),
+    (Ipv4Addr::new(240, 0, 0, 0), 4),
+];
+
+// only globally routable addresses may receive webhooks, so that they cannot
+// be used to reach services in the network of the server
+fn is_global(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !NON_GLOBAL_V4.iter().any(|(network, prefix)| 
+            u32::from(*ip) >> (32 - prefix) == u32::from(*network) >> (32 - prefix)),
+        // global unicast 2000::/3 except ranges for documentation, 
+        // Teredo and 6to4, the last two embed IPv4 addresses;
+        // mapped ::ffff:0:0/96 and NAT64 64:ff9b::/96 lie outside of it
+        IpAddr::V6(ip) => match ip.segments() {
+            [0x2001, 0x0000..=0x01ff, ..] | [0x2001, 0x0db8, ..] | [0x2002, ..] | [0x3ff0..=0x3fff, ..] => false,
+            [first, ..] => first & 0xe000 == 0x2000,
+        },
+    }
+}
+
+// the address deliveries connect to, the first one the host of the url resolves to, 
+// all of them must be global unless private ones are allowed
+async fn resolve(url: &Url, allow_private: bool) -> Result<SocketAddr, String> {
+    let host = url.host_str().ok_or("webhook url has no host")?;
+    let port = url.port_or_known_default().ok_or("webhook url has no port")?;
+    // IPv6 hosts are kept in brackets within url
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let addrs: Vec<SocketAddr> = (host, port).to_socket_addrs()
+        .await
+        .map_err(|err| err.to_string())?
+        .collect();
+    if !allow_private {
+        if let Some(addr) = addrs.iter().find(|addr| !is_global(&addr.ip())) {
+            return Err(format!("webhook url resolves to private address {}", addr.ip()));
+        }
+    }
+    addrs.into_iter()
+        .next()
+        .ok_or_else(|| "webhook url host has no address".to_string())
+}
+
+// fails if host of the url resolves to any address that is not global
+pub async fn ensure_public(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    resolve(&url, false).await.map(|_| ())
+}
+
+// failed attempt along with http status if receiver responded at all
+pub(crate) struct DeliveryError {
+    pub response_status: Option<i32>,
+    pub message: String,
+}
+
+// posts payload, any 2xx response counts as delivered, receiver
+// is checked again as its host may resolve differently by now
+pub(crate) async fn deliver(delivery: &PendingDelivery, allow_private: bool) -> Result<i32, DeliveryError> {
+    let failure = |message: String| DeliveryError { response_status: None, message };
+    let url = Url::parse(&delivery.url).map_err(|err| failure(err.to_string()))?;
+    let addr = resolve(&url, allow_private).await.map_err(failure)?;
+
+    let host = url.host_str().unwrap_or_default();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let mut req = Request::post(url.clone());
+    req.insert_header(http_types::headers::HOST, host);
+    req.insert_header(http_types::headers::CONTENT_TYPE, "application/json");
+    req.insert_header(SIGNATURE_HEADER, signature(&delivery.secret, &delivery.payload));
+    req.insert_header(EVENT_HEADER, delivery.event.as_str());
+    req.insert_header(DELIVERY_HEADER, delivery.id.to_string());
+    req.set_body(delivery.payload.as_str());
+
+    let res = async_std::future::timeout(DELIVERY_TIMEOUT, send(&url, addr, req))
+        .await
+        .map_err(|_| failure("delivery timed out".to_string()))?
+        .map_err(|err| failure(err.to_string()))?;
+
+    let status = res.status();
+    if status.is_success() {
+        Ok(status as i32)
+    } else {
+        Err(DeliveryError { 
+            response_status: Some(status as i32), 
+            message: format!("receiver responded with {}", status),
+        })
+    }
+}
+
+// request sent over connection to the checked address rather than to whatever 
+// the host resolves to when connecting, TLS certificate is verified for the host
+async fn send(url: &Url, addr: SocketAddr, req: Request) -> http_types::Result<Response> {
+    let stream = TcpStream::connect(addr).await?;
+    if url.scheme() == "https" {
+        let stream = async_native_tls::connect(url.host_str().unwrap_or_default(), stream).await?;
+        async_h1::connect(stream, req).await
+    } else {
+        async_h1::connect(stream, req).await
+    }
+}
@@ -4,22 +4,57 @@
 // Since I need them to run in order I packed them into one test.
 use once_cell::sync::OnceCell;
 use async_std::{print, println};
+use tide::listener::Listener;
 use realworld_tide_sqlite_backend::{
     config::Config, 
     backend::*, 
     errors, 
-    models::{article},
+    models::{article, reaction, notification, event, webhook, report, upload::Avatar},
     requests,
     filters,
+    webhooks,
+    storage::Storage,
 };
 
 static SERVER: OnceCell<Server> = OnceCell::new();
 
+// keeps uploaded files off the disk
+#[derive(Debug, Default)]
+struct MemoryStorage {
+    files: async_std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>,
+}
+
+#[async_trait::async_trait]
+impl Storage for MemoryStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), errors::BackendError> {
+        self.files.lock().await.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, errors::BackendError> {
+        self.files.lock().await.get(key).cloned()
+            .ok_or_else(|| errors::BackendError::NoUploadFound(key.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), errors::BackendError> {
+        self.files.lock().await.remove(key);
+        Ok(())
+    }
+}
+
 async fn drop_db_and_connect() -> Result<(), errors::BackendError> {
     let mut cfg = Config::from_env();
     cfg.drop_database = true;
+    cfg.moderators = vec!["graham_greene".to_string()];
+    cfg.max_thread_replies = 2;
+    // webhooks are delivered to a local receiver
+    cfg.webhook_allow_private = true;
+    cfg.upload_max_bytes = 1024;
+    cfg.upload_quota_bytes = 2048;
+    cfg.orphan_upload_minutes = 0;
 
-    let mut server = Server::with_config(cfg);
+    let mut server = Server::with_config(cfg)
+        .with_storage(std::sync::Arc::new(MemoryStorage::default()));
     server.connect().await?;
 
     SERVER.set(server).expect("Cannot create server instance.");
@@ -28,6 +63,11 @@ async fn drop_db_and_connect() -> Result<(), errors::BackendError> {
 
 #[async_std::test]
 async fn tests() -> Result<(), errors::BackendError> {
+    // The whole scenario is one large future; keep it on the heap.
+    Box::pin(run_tests()).await
+}
+
+async fn run_tests() -> Result<(), errors::BackendError> {
 
     drop_db_and_connect().await?;
     let server = SERVER.get().unwrap();
@@ -109,7 +149,7 @@ async fn tests() -> Result<(), errors::BackendError> {
 
     //
     print!("retrieving non-existing users profile...").await;
-    server.profile("graham_green").await.expect_err("Got profile of non-existing user.");
+    server.profile(None, "graham_green").await.expect_err("Got profile of non-existing user.");
     println!(" failed as expected.").await;
     
     // smith follows joyce
@@ -151,12 +191,13 @@ async fn tests() -> Result<(), errors::BackendError> {
     // test registered tags
     print!("getting tags ...").await;
     let tags = server.get_tags().await?;
-    assert_eq!(tags.tags, vec!["Dublin".to_string(), "Homer".to_string(), "Stream".to_string()]);
+    assert_eq!(tags.tags, vec!["dublin".to_string(), "homer".to_string(), "stream".to_string()]);
     println!(" done, they are: {}.", tags).await;
     
     // check if articles are in db
     print!("checking number of registered articles...").await;
     let articles_by_james_joyce = server.get_articles(
+        None,
         filters::ArticleFilterByValues::default().author("james_joyce".to_string()),
         filters::OrderByFilter::Descending("createdAt"),
         filters::LimitOffsetFilter::default()
@@ -235,11 +276,21 @@ async fn tests() -> Result<(), errors::BackendError> {
         "graham_greene").await?;
     assert_eq!(graham_green_profile.username, "graham_greene");
     println!(" done.").await;
+
+    // repeating follow and favorite notifies only once
+    print!("{} repeats follow and favorite...", scott_smith_logged_in.username).await;
+    let unread = server.unread_notifications_count(&graham_greene_logged_in.token).await?;
+    server.follow(&scott_smith_logged_in.token, "graham_greene").await?;
+    server.favorite_article(&scott_smith_logged_in.token, "the-quiet-american").await?;
+    let repeated = server.unread_notifications_count(&graham_greene_logged_in.token).await?;
+    assert_eq!(unread.unread_count, repeated.unread_count);
+    println!(" done, not notified again.").await;
     
     // feeds articles for smith
     print!("feed articles for user {}...", scott_smith_logged_in.username).await;
     let articles_fed_for_scott_smith = server.feed_articles(
         &scott_smith_logged_in.token, 
+        filters::FeedFilter::default(),
         filters::LimitOffsetFilter::default(),
     )
     .await?; 
@@ -250,6 +301,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let update_article_req = requests::article::UpdateArticleRequest {
         article: article::UpdateArticle::default().title("The Calm American"),
         slug: "the-quiet-american",
+        if_match: None,
     };
     print!("{} updates article {}...", graham_greene_logged_in.username, update_article_req.slug).await;
     server.update_article(
@@ -260,6 +312,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     println!(" done.").await;
     // check if the old slug is absent
     let articles_by_graham_green = server.get_articles(
+        None,
         filters::ArticleFilterByValues::default().slug("the-quiet-american".to_string()),
         filters::OrderByFilter::Descending("createdAt"),
         filters::LimitOffsetFilter::default()
@@ -268,6 +321,7 @@ async fn tests() -> Result<(), errors::BackendError> {
 
     // check if the new slug is there
     let articles_by_graham_green = server.get_articles(
+        None,
         filters::ArticleFilterByValues::default().slug("the-calm-american".to_string()),
         filters::OrderByFilter::Descending("createdAt"),
         filters::LimitOffsetFilter::default()
@@ -288,6 +342,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let update_article_req = requests::article::UpdateArticleRequest {
         article: article::UpdateArticle::default().title("The Mad American"),
         slug: "the-calm-american",
+        if_match: None,
     };
     print!("{} updates article {}...", scott_smith_logged_in.username, update_article_req.slug).await;    
     server.update_article(
@@ -312,6 +367,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let update_article_req = requests::article::UpdateArticleRequest {
         article: article::UpdateArticle::default().title("The Quiet American"),
         slug: "the-good-american",
+        if_match: None,
     };
     print!("{} updates non-existing article ...", scott_smith_logged_in.username).await;
     server.update_article(
@@ -326,6 +382,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let comment_req = requests::article::AddCommentRequest {
         article_slug: "the-calm-american",
         body: "Author, write more !".to_string(),
+        parent_id: None,
     };
 
     print!("{} adds a comment to article {}...", scott_smith_logged_in.username, 
@@ -340,6 +397,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let comment_req = requests::article::AddCommentRequest {
         article_slug: "the-calm-american",
         body: "Hmmm...".to_string(),
+        parent_id: None,
     };
     print!("{} adds a comment to article {}...", scott_smith_logged_in.username, 
         comment_req.article_slug).await;
@@ -353,6 +411,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let comment_req = requests::article::AddCommentRequest {
         article_slug: "the-calm-american",
         body: "Should call it The Quiet American".to_string(),
+        parent_id: None,
     };
     print!("{} adds a comment to article {}...", james_joyce_logged_in.username, 
         comment_req.article_slug).await;
@@ -366,6 +425,7 @@ async fn tests() -> Result<(), errors::BackendError> {
     let comment_req = requests::article::AddCommentRequest {
         article_slug: "the-calm-american",
         body: "I love all my writings".to_string(),
+        parent_id: None,
     };
     print!("{} adds a comment to article {}...", graham_greene_logged_in.username, 
         comment_req.article_slug).await;
@@ -379,7 +439,10 @@ async fn tests() -> Result<(), errors::BackendError> {
 
     let article_slug = "the-calm-american";
     
-    let comments = server.get_comments(article_slug).await?;
+    let comments = server.get_comments(None, article_slug, 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
     assert_eq!(4, comments.comments.len());
 
     // delete comment
@@ -399,7 +462,10 @@ async fn tests() -> Result<(), errors::BackendError> {
     // check if comment has been really deleted
     let article_slug = "the-calm-american";
 
-    let comments = server.get_comments(article_slug).await?;
+    let comments = server.get_comments(None, article_slug, 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
     assert_eq!(3, comments.comments.len());
 
     // delete non-existing comment
@@ -417,5 +483,1610 @@ async fn tests() -> Result<(), errors::BackendError> {
     .expect_err("Deleting non-belonging comment.");
     println!(" failed as expected.").await;
 
+    // create article with already taken title
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "ulysses".to_string(),
+        title: "Ulysses".to_string(), 
+        description: None,
+        body: "Same title, different story".to_string(), 
+        tag_list: None,
+    };
+    print!("{} creates an article with taken title ...", graham_greene_logged_in.username).await;
+    let article_response = server.create_article(
+        &graham_greene_logged_in.token, 
+        create_article).await?;
+    assert_eq!(article_response.article.slug, "ulysses-2");
+    println!(" done, slug: {}.", article_response.article.slug).await;
+
+    // get article by its slug before title change
+    print!("getting article by old slug {}...", "the-quiet-american").await;
+    let article_response = server.get_article_by_slug(None, "the-quiet-american").await?;
+    assert_eq!(article_response.article.slug, "the-calm-american");
+    println!(" done, current slug: {}.", article_response.article.slug).await;
+
+    // old slugs are not reused by other articles
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "the-quiet-american".to_string(),
+        title: "The Quiet American".to_string(), 
+        description: None,
+        body: "Some body".to_string(), 
+        tag_list: None,
+    };
+    print!("{} creates an article with title from slug history ...", james_joyce_logged_in.username).await;
+    let article_response = server.create_article(
+        &james_joyce_logged_in.token, 
+        create_article).await?;
+    assert_eq!(article_response.article.slug, "the-quiet-american-2");
+    println!(" done, slug: {}.", article_response.article.slug).await;
+
+    // author gets back the old title and reclaims its slug
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().title("The Quiet American"),
+        slug: "the-calm-american",
+        if_match: None,
+    };
+    print!("{} renames article back to its old title...", graham_greene_logged_in.username).await;
+    let article_response = server.update_article(
+        &graham_greene_logged_in.token, 
+        update_article_req,
+    )
+    .await?;
+    assert_eq!(article_response.article.slug, "the-quiet-american");
+    let article_response = server.get_article_by_slug(None, "the-calm-american").await?;
+    assert_eq!(article_response.article.slug, "the-quiet-american");
+    println!(" done.").await;
+
+    // update article based on its current version
+    let version = article_response.article.version;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("Revised body"),
+        slug: "the-quiet-american",
+        if_match: Some(version),
+    };
+    print!("{} updates article version {}...", graham_greene_logged_in.username, version).await;
+    let article_response = server.update_article(
+        &graham_greene_logged_in.token, 
+        update_article_req,
+    )
+    .await?;
+    assert_eq!(article_response.article.version, version + 1);
+    println!(" done.").await;
+
+    // update article based on stale version
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("Concurrently revised body"),
+        slug: "the-quiet-american",
+        if_match: Some(version),
+    };
+    print!("{} updates stale article version {}...", graham_greene_logged_in.username, version).await;
+    match server.update_article(&graham_greene_logged_in.token, update_article_req).await {
+        Err(errors::BackendError::PreconditionFailed) => (),
+        res => panic!("Updated stale article version: {:?}", res),
+    }
+    println!(" failed as expected.").await;
+
+    // update user profile based on stale version
+    let set_profile_james_joyce = requests::user::UserUpdateRequest {
+        bio: Some("Im writing another novel".to_string()),
+        if_match: Some(profile_james_joyce.version - 1),
+        ..Default::default()
+    };
+    print!("updating users {} stale profile...", james_joyce_logged_in.username).await;
+    match server.update_user(&james_joyce_logged_in.token, set_profile_james_joyce).await {
+        Err(errors::BackendError::PreconditionFailed) => (),
+        res => panic!("Updated stale user version: {:?}", res),
+    }
+    println!(" failed as expected.").await;
+
+    print!("updating users {} profile concurrently from the same version...", james_joyce_logged_in.username).await;
+    let version = server.user_by_token(&james_joyce_logged_in.token).await?.version;
+    let updates = ["Writing Ulysses", "Writing Finnegans Wake"].into_iter().map(|bio| {
+        let token = james_joyce_logged_in.token.clone();
+        async_std::task::spawn(async move {
+            let set_profile = requests::user::UserUpdateRequest {
+                bio: Some(bio.to_string()),
+                if_match: Some(version),
+                ..Default::default()
+            };
+            server.update_user(&token, set_profile).await
+        })
+    }).collect::<Vec<_>>();
+    let mut updated = Vec::new();
+    for update in updates {
+        match update.await {
+            Ok(user) => updated.push(user.bio),
+            Err(errors::BackendError::PreconditionFailed) => (),
+            res => panic!("Concurrent update failed: {:?}", res),
+        }
+    }
+    // the one which failed has not written its bio
+    assert_eq!(updated.len(), 1);
+    assert_eq!(server.profile(None, "james_joyce").await?.bio, Some(updated[0].clone()));
+    println!(" done, one failed.").await;
+
+    // reply to a comment and reply to the reply
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Agree, waiting for more".to_string(),
+        parent_id: Some(1),
+    };
+    print!("{} replies to comment {}...", james_joyce_logged_in.username, 1).await;
+    let reply = server.add_comment(
+        &james_joyce_logged_in.token, 
+        comment_req
+    )
+    .await?;
+    assert_eq!(reply.comment.depth, 1);
+    println!(" done.").await;
+
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Me too".to_string(),
+        parent_id: Some(reply.comment.id),
+    };
+    print!("{} replies to comment {}...", graham_greene_logged_in.username, reply.comment.id).await;
+    let reply_to_reply = server.add_comment(
+        &graham_greene_logged_in.token, 
+        comment_req
+    )
+    .await?;
+    assert_eq!(reply_to_reply.comment.depth, 2);
+    println!(" done.").await;
+
+    // replies follow the replied comment
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![3, 2, 1, reply.comment.id, reply_to_reply.comment.id]);
+
+    // invalid token on a public read is treated as anonymous
+    print!("getting comments with an invalid token...").await;
+    let comments = server.get_comments(Some("invalid.token"), "the-quiet-american", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    assert_eq!(comments.comments.len(), 5);
+    println!(" done, read anonymously.").await;
+
+    // deleting comment having replies keeps the thread
+    let delete_by = requests::article::DeleteCommentRequest {
+        id: 1,
+        article_slug: "the-quiet-american",
+    };
+    print!("{} deletes a replied comment to article {}...", scott_smith_logged_in.username, 
+        delete_by.article_slug).await;
+    server.delete_comment(
+        &scott_smith_logged_in.token, 
+        delete_by,
+    )
+    .await?;
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    assert_eq!(5, comments.comments.len());
+    assert!(comments.comments[2].comment.deleted);
+    assert_eq!(comments.comments[2].comment.body, "");
+    println!(" done, comment is tombstoned.").await;
+
+    // tombstoned comment cannot be replied
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Where did it go ?".to_string(),
+        parent_id: Some(1),
+    };
+    print!("{} replies to deleted comment...", james_joyce_logged_in.username).await;
+    server.add_comment(&james_joyce_logged_in.token, comment_req)
+        .await
+        .expect_err("Replied to deleted comment.");
+    println!(" failed as expected.").await;
+
+    // edit own comment
+    let update_req = requests::article::UpdateCommentRequest {
+        id: 2,
+        body: "Hmmm... not bad".to_string(),
+        article_slug: "the-quiet-american",
+    };
+    print!("{} edits a comment to article {}...", scott_smith_logged_in.username, 
+        update_req.article_slug).await;
+    let comment = server.update_comment(
+        &scott_smith_logged_in.token, 
+        update_req,
+    )
+    .await?;
+    assert_eq!(comment.comment.body, "Hmmm... not bad");
+    assert!(comment.comment.edited);
+    println!(" done.").await;
+
+    // edit not owned comment
+    let update_req = requests::article::UpdateCommentRequest {
+        id: 2,
+        body: "Hmmm... bad".to_string(),
+        article_slug: "the-quiet-american",
+    };
+    print!("{} edits a (not owned) comment to article {}...", james_joyce_logged_in.username, 
+        update_req.article_slug).await;
+    match server.update_comment(&james_joyce_logged_in.token, update_req).await {
+        Err(errors::BackendError::Forbidden) => (),
+        res => panic!("Edited non-belonging comment: {:?}", res),
+    }
+    println!(" failed for forbidden.").await;
+
+    // page through comment threads
+    print!("paging through comments to article {}...", "the-quiet-american").await;
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default().limit(2)
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![3, 2]);
+    assert_eq!(comments.comments_count, 4);
+
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default().cursor(2), 
+        filters::LimitOffsetFilter::default().limit(2)
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![1, reply.comment.id, reply_to_reply.comment.id]);
+
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default().order(filters::CommentOrder::Oldest), 
+        filters::LimitOffsetFilter::default().limit(2)
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![1, reply.comment.id, reply_to_reply.comment.id, 2]);
+
+    // page holds at least one thread
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default().limit(0)
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![3]);
+    println!(" done.").await;
+
+    // replies returned with a page of threads are capped
+    print!("{} replies beyond the replies limit...", graham_greene_logged_in.username).await;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Coming soon".to_string(),
+        parent_id: Some(reply_to_reply.comment.id),
+    };
+    let extra_reply = server.add_comment(&graham_greene_logged_in.token, comment_req).await?;
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default().order(filters::CommentOrder::Oldest), 
+        filters::LimitOffsetFilter::default().limit(1)
+    ).await?;
+    let ids = comments.comments.iter().map(|comment| comment.comment.id).collect::<Vec<i32>>();
+    assert_eq!(ids, vec![1, reply.comment.id, reply_to_reply.comment.id]);
+
+    let delete_by = requests::article::DeleteCommentRequest {
+        id: extra_reply.comment.id,
+        article_slug: "the-quiet-american",
+    };
+    server.delete_comment(&graham_greene_logged_in.token, delete_by).await?;
+    println!(" done, only {} replies returned.", 2).await;
+
+    // react to article and comment
+    print!("{} reacts to article {}...", scott_smith_logged_in.username, "the-quiet-american").await;
+    server.react_to_article(&scott_smith_logged_in.token, "the-quiet-american", 
+        reaction::ReactionKind::Heart).await?;
+    let article_response = server.react_to_article(&james_joyce_logged_in.token, "the-quiet-american", 
+        "❤️".parse()?).await?;
+    let hearts = article_response.reactions.iter()
+        .find(|reaction| reaction.kind == reaction::ReactionKind::Heart)
+        .unwrap();
+    assert_eq!(hearts.count, 2);
+    assert!(hearts.reacted);
+
+    let article_response = server.unreact_to_article(&james_joyce_logged_in.token, "the-quiet-american", 
+        reaction::ReactionKind::Heart).await?;
+    let hearts = article_response.reactions.iter()
+        .find(|reaction| reaction.kind == reaction::ReactionKind::Heart)
+        .unwrap();
+    assert_eq!(hearts.count, 1);
+    assert!(!hearts.reacted);
+    println!(" done.").await;
+
+    print!("{} reacts to comment {}...", graham_greene_logged_in.username, 2).await;
+    let comment = server.react_to_comment(&graham_greene_logged_in.token, "the-quiet-american", 2, 
+        reaction::ReactionKind::Thinking).await?;
+    assert!(comment.reactions.iter()
+        .any(|reaction| reaction.kind == reaction::ReactionKind::Thinking && reaction.count == 1 && reaction.reacted));
+    println!(" done.").await;
+
+    // notifications about follows, favorites and comments
+    print!("getting notifications of {}...", graham_greene_logged_in.username).await;
+    let notifications = server.get_notifications(&graham_greene_logged_in.token, 
+        filters::NotificationFilter::default().unread(true),
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    assert_eq!(notifications.unread_count, 7);
+    assert_eq!(notifications.notifications.len(), 7);
+    assert!(notifications.notifications.iter()
+        .any(|notification| notification.notification.kind == notification::NotificationKind::Follow));
+    println!(" done, {} unread.", notifications.unread_count).await;
+
+    let id = notifications.notifications[0].notification.id;
+    print!("{} marks notification {} read...", graham_greene_logged_in.username, id).await;
+    let notification = server.mark_notification_read(&graham_greene_logged_in.token, id).await?;
+    assert!(notification.notification.read);
+    let unread = server.unread_notifications_count(&graham_greene_logged_in.token).await?;
+    assert_eq!(unread.unread_count, 6);
+    println!(" done.").await;
+
+    print!("{} marks notification {} of another user read...", scott_smith_logged_in.username, id).await;
+    server.mark_notification_read(&scott_smith_logged_in.token, id)
+        .await
+        .expect_err("Marked notification of another user.");
+    println!(" failed as expected.").await;
+
+    // muted notifications are not delivered
+    print!("{} mutes comment notifications...", graham_greene_logged_in.username).await;
+    let preferences = server.update_notification_preferences(&graham_greene_logged_in.token, 
+        notification::NotificationPreferences { muted: vec![notification::NotificationKind::Comment] }
+    ).await?;
+    assert_eq!(preferences.muted, vec![notification::NotificationKind::Comment]);
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Anybody here ?".to_string(),
+        parent_id: None,
+    };
+    server.add_comment(&scott_smith_logged_in.token, comment_req).await?;
+    let unread = server.unread_notifications_count(&graham_greene_logged_in.token).await?;
+    assert_eq!(unread.unread_count, 6);
+    println!(" done.").await;
+
+    print!("{} marks all notifications read...", graham_greene_logged_in.username).await;
+    let unread = server.mark_all_notifications_read(&graham_greene_logged_in.token).await?;
+    assert_eq!(unread.unread_count, 0);
+    println!(" done.").await;
+
+    // real-time events
+    print!("{} subscribes to events viewing {}...", scott_smith_logged_in.username, "the-quiet-american").await;
+    let mut subscription = server.subscribe(&scott_smith_logged_in.token, 
+        Some("the-quiet-american".to_string()), None).await?;
+    assert!(subscription.try_next().is_none());
+    let mut not_following_subscription = server.subscribe(&graham_greene_logged_in.token, None, None).await?;
+    println!(" done.").await;
+
+    print!("{} creates an article followed by {}...", james_joyce_logged_in.username, scott_smith_logged_in.username).await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "dubliners".to_string(),
+        title: "Dubliners".to_string(), 
+        description: None,
+        body: "Fifteen short stories".to_string(), 
+        tag_list: None,
+    };
+    server.create_article(&james_joyce_logged_in.token, create_article).await?;
+    let article_event = subscription.try_next().expect("No article event received.");
+    assert_eq!(article_event.kind, event::EventKind::Article);
+    assert_eq!(article_event.article_slug.as_deref(), Some("dubliners"));
+    // stored once for all followers
+    assert!(article_event.recipient.is_none());
+    assert_eq!(article_event.author.as_deref(), Some("james_joyce"));
+    assert!(not_following_subscription.try_next().is_none());
+    println!(" done, event {} received.", article_event.id).await;
+
+    print!("{} comments own article viewed by {}...", graham_greene_logged_in.username, scott_smith_logged_in.username).await;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Thanks for reading".to_string(),
+        parent_id: None,
+    };
+    server.add_comment(&graham_greene_logged_in.token, comment_req).await?;
+    let comment_event = subscription.try_next().expect("No comment event received.");
+    assert_eq!(comment_event.kind, event::EventKind::Comment);
+    assert!(comment_event.recipient.is_none());
+    assert!(subscription.try_next().is_none());
+    println!(" done.").await;
+
+    // pruning keeps events within the replay buffer
+    server.prune_events().await?;
+    print!("{} reconnects, replaying from event {}...", scott_smith_logged_in.username, article_event.id).await;
+    let mut subscription = server.subscribe(&scott_smith_logged_in.token, 
+        None, Some(article_event.id - 1)).await?;
+    assert_eq!(subscription.try_next().map(|event| event.id), Some(article_event.id));
+    // comments are not replayed when not viewing the article
+    assert!(subscription.try_next().is_none());
+    println!(" done.").await;
+
+    // webhooks, delivered to local http receiver
+    print!("starting local webhook receiver...").await;
+    let (received_sender, received) = async_std::channel::unbounded::<(String, String, String)>();
+    let mut receiver = tide::with_state(received_sender);
+    receiver.at("/hooks").post(|mut req: tide::Request<async_std::channel::Sender<(String, String, String)>>| async move {
+        let payload = req.body_string().await?;
+        let header = |name| req.header(name).map(|hdr| hdr.as_str().to_string()).unwrap_or_default();
+        let received = (header(webhooks::EVENT_HEADER), header(webhooks::SIGNATURE_HEADER), payload);
+        req.state().send(received).await?;
+        Ok(tide::StatusCode::Ok)
+    });
+    let mut listener = receiver.bind("127.0.0.1:0")
+        .await
+        .map_err(|err| errors::BackendError::WebServerConnectionFailure(err.to_string()))?;
+    let receiver_url = format!("{}/hooks", listener.info()[0].connection());
+    async_std::task::spawn(async move { listener.accept().await });
+    println!(" done, listening at {}.", receiver_url).await;
+
+    print!("checking webhook urls of private addresses...").await;
+    webhooks::ensure_public("http://169.254.169.254/latest/meta-data")
+        .await
+        .expect_err("Accepted link-local webhook url.");
+    webhooks::ensure_public("http://10.0.0.1:8080/hooks")
+        .await
+        .expect_err("Accepted private webhook url.");
+    webhooks::ensure_public("http://[::1]/hooks")
+        .await
+        .expect_err("Accepted loopback webhook url.");
+    for url in ["http://0.0.0.1/", "http://192.0.0.8/", "http://198.18.0.1/", "http://224.0.0.1/", 
+        "http://240.0.0.1/", "http://[::ffff:127.0.0.1]/", "http://[64:ff9b::a9fe:a9fe]/", "http://[fc00::1]/"] {
+        webhooks::ensure_public(url)
+            .await
+            .expect_err("Accepted non-global webhook url.");
+    }
+    webhooks::ensure_public("http://[2606:2800:220:1:248:1893:25c8:1946]/hooks").await
+        .map_err(errors::BackendError::ValidationError)?;
+    webhooks::ensure_public("http://93.184.216.34/hooks").await
+        .map_err(errors::BackendError::ValidationError)?;
+    println!(" done, rejected.").await;
+
+    print!("{} registers webhook with short secret...", james_joyce_logged_in.username).await;
+    let webhook_req = requests::webhook::CreateWebhookRequest {
+        url: receiver_url.clone(),
+        secret: "secret".to_string(),
+        events: vec![webhook::WebhookEvent::ArticleCreated],
+    };
+    server.register_webhook(&james_joyce_logged_in.token, webhook_req)
+        .await
+        .expect_err("Registered webhook with short secret.");
+    println!(" failed as expected.").await;
+
+    print!("{} registers webhook...", scott_smith_logged_in.username).await;
+    let webhook_secret = "dublin-is-a-fine-city";
+    let webhook_req = requests::webhook::CreateWebhookRequest {
+        url: receiver_url.clone(),
+        secret: webhook_secret.to_string(),
+        events: vec![webhook::WebhookEvent::ArticleCreated, webhook::WebhookEvent::CommentCreated],
+    };
+    let webhook_response = server.register_webhook(&scott_smith_logged_in.token, webhook_req).await?;
+    assert_eq!(webhook_response.events.len(), 2);
+    println!(" done, id {}.", webhook_response.webhook.id).await;
+
+    print!("{} registers webhook...", james_joyce_logged_in.username).await;
+    let webhook_req = requests::webhook::CreateWebhookRequest {
+        url: receiver_url.clone(),
+        secret: webhook_secret.to_string(),
+        events: vec![webhook::WebhookEvent::ArticleCreated],
+    };
+    let other_webhook = server.register_webhook(&james_joyce_logged_in.token, webhook_req).await?;
+    println!(" done, id {}.", other_webhook.webhook.id).await;
+
+    print!("{} creates an article delivered to webhook...", scott_smith_logged_in.username).await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "notes".to_string(),
+        title: "Notes".to_string(), 
+        description: None,
+        body: "Notes on webhooks".to_string(), 
+        tag_list: None,
+    };
+    server.create_article(&scott_smith_logged_in.token, create_article).await?;
+    assert_eq!(server.deliver_webhooks().await?, 1);
+    let (event, signature, payload) = received.recv().await.expect("No webhook received.");
+    assert_eq!(event, "article.created");
+    assert_eq!(signature, webhooks::signature(webhook_secret, &payload));
+    assert!(payload.contains("\"slug\":\"notes\""));
+    // nothing left to deliver
+    assert_eq!(server.deliver_webhooks().await?, 0);
+    let deliveries = server.webhook_deliveries(&scott_smith_logged_in.token, 
+        webhook_response.webhook.id, filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(deliveries.deliveries_count, 1);
+    assert_eq!(deliveries.deliveries[0].status, webhook::DeliveryStatus::Delivered);
+    // article of another author is not delivered to webhook of james joyce
+    let deliveries = server.webhook_deliveries(&james_joyce_logged_in.token, 
+        other_webhook.webhook.id, filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(deliveries.deliveries_count, 0);
+    server.delete_webhook(&james_joyce_logged_in.token, other_webhook.webhook.id).await?;
+    println!(" done.").await;
+
+    print!("{} registers webhook with unreachable receiver...", graham_greene_logged_in.username).await;
+    let webhook_req = requests::webhook::CreateWebhookRequest {
+        url: "http://127.0.0.1:1/hooks".to_string(),
+        secret: webhook_secret.to_string(),
+        events: vec![webhook::WebhookEvent::UserFollowed],
+    };
+    let unreachable_webhook = server.register_webhook(&graham_greene_logged_in.token, webhook_req).await?;
+    server.follow(&james_joyce_logged_in.token, "graham_greene").await?;
+    assert_eq!(server.deliver_webhooks().await?, 0);
+    let deliveries = server.webhook_deliveries(&graham_greene_logged_in.token, 
+        unreachable_webhook.webhook.id, filters::LimitOffsetFilter::default()).await?;
+    let delivery = &deliveries.deliveries[0];
+    assert_eq!(delivery.status, webhook::DeliveryStatus::Pending);
+    assert_eq!(delivery.attempts, 1);
+    assert!(delivery.last_error.is_some());
+    // retried later with backoff
+    assert!(delivery.next_attempt_at.unwrap() > delivery.created_at);
+    println!(" done, delivery rescheduled.").await;
+
+    print!("{} gets deliveries of webhook of another user...", james_joyce_logged_in.username).await;
+    server.webhook_deliveries(&james_joyce_logged_in.token, 
+        unreachable_webhook.webhook.id, filters::LimitOffsetFilter::default())
+        .await
+        .expect_err("Got deliveries of webhook of another user.");
+    println!(" failed as expected.").await;
+
+    print!("{} deletes webhook...", graham_greene_logged_in.username).await;
+    server.delete_webhook(&graham_greene_logged_in.token, unreachable_webhook.webhook.id).await?;
+    let webhooks = server.get_webhooks(&graham_greene_logged_in.token).await?;
+    assert!(webhooks.webhooks.is_empty());
+    println!(" done.").await;
+
+    // follower and following lists
+    print!("getting followers of {}...", graham_greene_logged_in.username).await;
+    let followers = server.followers(None, "graham_greene", filters::LimitOffsetFilter::default().limit(1)).await?;
+    assert_eq!(followers.profiles_count, 2);
+    assert_eq!(followers.profiles.len(), 1);
+    assert_eq!(followers.profiles[0].username, "james_joyce");
+    println!(" done, {} followers.", followers.profiles_count).await;
+
+    print!("getting users followed by {}...", scott_smith_logged_in.username).await;
+    let following = server.following(None, "scott_smith", filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(following.profiles_count, 2);
+    assert!(following.profiles.iter().any(|profile| profile.username == "graham_greene"));
+    server.following(None, "graham_green", filters::LimitOffsetFilter::default())
+        .await
+        .expect_err("Got following list of non-existing user.");
+    println!(" done.").await;
+
+    print!("getting profile counts of {}...", graham_greene_logged_in.username).await;
+    let profile = server.profile(None, "graham_greene").await?;
+    assert_eq!(profile.followers_count, 2);
+    assert_eq!(profile.following_count, 0);
+    assert_eq!(profile.articles_count, 2);
+    println!(" done.").await;
+
+    // blocking
+    print!("{} blocks {}...", graham_greene_logged_in.username, scott_smith_logged_in.username).await;
+    server.block(&graham_greene_logged_in.token, "scott_smith").await?;
+    let profile = server.profile(None, "graham_greene").await?;
+    assert_eq!(profile.followers_count, 1);
+    println!(" done, {} unfollowed.", scott_smith_logged_in.username).await;
+
+    print!("blocked {} tries to reach {}...", scott_smith_logged_in.username, graham_greene_logged_in.username).await;
+    server.profile(Some(&scott_smith_logged_in.token), "graham_greene")
+        .await
+        .expect_err("Blocked user got profile.");
+    match server.follow(&scott_smith_logged_in.token, "graham_greene").await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Blocked user followed."),
+    }
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-quiet-american",
+        body: "Hello ?".to_string(),
+        parent_id: None,
+    };
+    match server.add_comment(&scott_smith_logged_in.token, comment_req).await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Blocked user commented."),
+    }
+    println!(" failed as expected.").await;
+
+    print!("{} unblocks {}...", graham_greene_logged_in.username, scott_smith_logged_in.username).await;
+    server.unblock(&graham_greene_logged_in.token, "scott_smith").await?;
+    server.profile(Some(&scott_smith_logged_in.token), "graham_greene").await?;
+    println!(" done.").await;
+
+    // muting
+    print!("{} mutes {}...", scott_smith_logged_in.username, james_joyce_logged_in.username).await;
+    server.mute(&scott_smith_logged_in.token, "james_joyce").await?;
+    let articles = server.get_articles(
+        Some(&scott_smith_logged_in.token),
+        filters::ArticleFilterByValues::default(),
+        filters::OrderByFilter::Descending("createdAt"),
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    assert!(!articles.articles.is_empty());
+    assert!(articles.articles.iter().all(|article| article.article.author != "james_joyce"));
+    let feed = server.feed_articles(&scott_smith_logged_in.token, 
+        filters::FeedFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(feed.articles.iter().all(|article| article.article.author != "james_joyce"));
+    println!(" done, muted articles hidden.").await;
+
+    print!("{} mutes {} in comments...", graham_greene_logged_in.username, scott_smith_logged_in.username).await;
+    let all_comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    server.mute(&graham_greene_logged_in.token, "scott_smith").await?;
+    let comments = server.get_comments(Some(&graham_greene_logged_in.token), "the-quiet-american", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(comments.comments_count < all_comments.comments_count);
+    assert!(comments.comments.iter()
+        .all(|comment| comment.author.as_ref().unwrap().username != "scott_smith"));
+    println!(" done.").await;
+
+    // reporting and moderation
+    print!("{} reports article without reason...", james_joyce_logged_in.username).await;
+    server.report_article(&james_joyce_logged_in.token, "notes", 
+        requests::report::ReportRequest { reason: "".to_string() })
+        .await
+        .expect_err("Reported article without reason.");
+    println!(" failed as expected.").await;
+
+    print!("{} reports article {}...", james_joyce_logged_in.username, "notes").await;
+    let article_report = server.report_article(&james_joyce_logged_in.token, "notes", 
+        requests::report::ReportRequest { reason: "spam".to_string() }).await?;
+    assert_eq!(article_report.status, report::ReportStatus::Open);
+    assert_eq!(article_report.author, "scott_smith");
+    println!(" done.").await;
+
+    print!("{} gets moderation queue...", scott_smith_logged_in.username).await;
+    match server.get_reports(&scott_smith_logged_in.token, 
+        filters::ReportFilter::default(), filters::LimitOffsetFilter::default()).await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Non-moderator got moderation queue."),
+    }
+    println!(" failed for forbidden.").await;
+
+    print!("{} hides reported article...", graham_greene_logged_in.username).await;
+    let reports = server.get_reports(&graham_greene_logged_in.token, 
+        filters::ReportFilter::default().status(report::ReportStatus::Open), 
+        filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(reports.reports_count, 1);
+    let moderated = server.moderate_report(&graham_greene_logged_in.token, article_report.id, 
+        requests::report::ModerationRequest { action: report::ModerationAction::Hide }).await?;
+    assert_eq!(moderated.status, report::ReportStatus::Actioned);
+    match server.get_article_by_slug(Some(&james_joyce_logged_in.token), "notes").await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Got hidden article."),
+    }
+    if !matches!(server.subscribe(&james_joyce_logged_in.token, Some("notes".to_string()), None).await, 
+        Err(errors::BackendError::Forbidden)) {
+        panic!("Subscribed to hidden article.");
+    }
+    // author still can see it
+    server.get_article_by_slug(Some(&scott_smith_logged_in.token), "notes").await?;
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default().author("scott_smith".to_string()),
+        filters::OrderByFilter::Descending("createdAt"),
+        filters::LimitOffsetFilter::default()).await?;
+    assert!(articles.articles.iter().all(|article| article.article.slug != "notes"));
+    server.moderate_report(&graham_greene_logged_in.token, article_report.id, 
+        requests::report::ModerationRequest { action: report::ModerationAction::Hide })
+        .await
+        .expect_err("Moderated resolved report.");
+    println!(" done.").await;
+
+    print!("{} reports comment of {}...", james_joyce_logged_in.username, scott_smith_logged_in.username).await;
+    let comments = server.get_comments(None, "the-quiet-american", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    let comment_id = comments.comments.iter()
+        .find(|comment| comment.author.as_ref().unwrap().username == "scott_smith")
+        .unwrap()
+        .comment.id;
+    let comment_report = server.report_comment(&james_joyce_logged_in.token, "the-quiet-american", comment_id,
+        requests::report::ReportRequest { reason: "rude".to_string() }).await?;
+    assert_eq!(comment_report.comment_id, Some(comment_id));
+    server.update_notification_preferences(&scott_smith_logged_in.token, 
+        notification::NotificationPreferences { muted: vec![notification::NotificationKind::Warning] })
+        .await
+        .expect_err("Muted moderator warnings.");
+    server.moderate_report(&graham_greene_logged_in.token, comment_report.id, 
+        requests::report::ModerationRequest { action: report::ModerationAction::Warn }).await?;
+    let warnings = server.get_notifications(&scott_smith_logged_in.token, 
+        filters::NotificationFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(warnings.notifications.iter()
+        .any(|notification| notification.notification.kind == notification::NotificationKind::Warning));
+    println!(" done, author warned.").await;
+
+    print!("{} dismisses report...", graham_greene_logged_in.username).await;
+    let dismissed_report = server.report_article(&scott_smith_logged_in.token, "dubliners", 
+        requests::report::ReportRequest { reason: "boring".to_string() }).await?;
+    let dismissed_report = server.dismiss_report(&graham_greene_logged_in.token, dismissed_report.id).await?;
+    assert_eq!(dismissed_report.status, report::ReportStatus::Dismissed);
+    server.get_article_by_slug(None, "dubliners").await?;
+    println!(" done.").await;
+
+    let reg_evelyn_waugh = requests::user::UserReg { 
+        username: "evelyn_waugh".to_string(),
+        email: "evelyn.waugh@fakemail.com".to_string(),
+        password: "password".to_string(),
+    };
+    print!("registering user {} (will take some secs)...", reg_evelyn_waugh.username).await;
+    let evelyn_waugh_logged_in = server.register_user(reg_evelyn_waugh).await?;
+    println!(" done.").await;
+
+    print!("{} suspends author of reported article...", graham_greene_logged_in.username).await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "scoop".to_string(),
+        title: "Scoop".to_string(), 
+        description: None,
+        body: "Fake news".to_string(), 
+        tag_list: None,
+    };
+    server.create_article(&evelyn_waugh_logged_in.token, create_article).await?;
+    let suspend_report = server.report_article(&james_joyce_logged_in.token, "scoop", 
+        requests::report::ReportRequest { reason: "misinformation".to_string() }).await?;
+    server.moderate_report(&graham_greene_logged_in.token, suspend_report.id, 
+        requests::report::ModerationRequest { action: report::ModerationAction::Suspend }).await?;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "decline-and-fall".to_string(),
+        title: "Decline and Fall".to_string(), 
+        description: None,
+        body: "More fake news".to_string(), 
+        tag_list: None,
+    };
+    match server.create_article(&evelyn_waugh_logged_in.token, create_article).await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Suspended user created article."),
+    }
+    println!(" done.").await;
+
+    // soft deletion and restoring
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "exiles".to_string(),
+        title: "Exiles".to_string(), 
+        description: None,
+        body: "A play in three acts".to_string(), 
+        tag_list: Some(vec!["Play".to_string()]),
+    };
+    server.create_article(&james_joyce_logged_in.token, create_article).await?;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "exiles",
+        body: "Unreadable".to_string(),
+        parent_id: None,
+    };
+    let comment = server.add_comment(&graham_greene_logged_in.token, comment_req).await?;
+
+    print!("{} deletes and restores comment...", graham_greene_logged_in.username).await;
+    server.delete_comment(&graham_greene_logged_in.token, requests::article::DeleteCommentRequest {
+        id: comment.comment.id,
+        article_slug: "exiles",
+    }).await?;
+    let comments = server.get_comments(None, "exiles", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(comments.comments.is_empty());
+    server.restore_comment(&james_joyce_logged_in.token, "exiles", comment.comment.id)
+        .await
+        .expect_err("Restored comment of another user.");
+    let restored = server.restore_comment(&graham_greene_logged_in.token, "exiles", comment.comment.id).await?;
+    assert_eq!(restored.comment.body, "Unreadable");
+    println!(" done.").await;
+
+    print!("{} deletes article {}...", james_joyce_logged_in.username, "exiles").await;
+    server.delete_article(&james_joyce_logged_in.token, "exiles").await?;
+    server.get_article_by_slug(None, "exiles")
+        .await
+        .expect_err("Got deleted article.");
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default().author("james_joyce".to_string()),
+        filters::OrderByFilter::Descending("createdAt"),
+        filters::LimitOffsetFilter::default()).await?;
+    assert!(articles.articles.iter().all(|article| article.article.slug != "exiles"));
+    assert!(!server.get_tags().await?.tags.contains(&"play".to_string()));
+    let comments = server.get_comments(None, "exiles", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(comments.comments.is_empty());
+    server.delete_article(&james_joyce_logged_in.token, "exiles")
+        .await
+        .expect_err("Deleted article twice.");
+    println!(" done.").await;
+
+    print!("{} restores article {}...", james_joyce_logged_in.username, "exiles").await;
+    server.restore_article(&graham_greene_logged_in.token, "exiles")
+        .await
+        .expect_err("Restored article of another user.");
+    let restored = server.restore_article(&james_joyce_logged_in.token, "exiles").await?;
+    assert_eq!(restored.article.slug, "exiles");
+    let comments = server.get_comments(None, "exiles", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(comments.comments.len(), 1);
+    println!(" done.").await;
+
+    print!("purging deleted content within restore window...").await;
+    server.delete_article(&james_joyce_logged_in.token, "exiles").await?;
+    assert_eq!(server.purge_deleted().await?, 0);
+    server.restore_article(&james_joyce_logged_in.token, "exiles").await?;
+    println!(" done, nothing purged.").await;
+
+    print!("purging deleted content past restore window...").await;
+    let mut cfg = Config::from_env();
+    cfg.drop_database = false;
+    cfg.restore_window_minutes = 0;
+    let mut purging_server = Server::with_config(cfg);
+    purging_server.connect().await?;
+
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "exiles",
+        body: "Is it a play ?".to_string(),
+        parent_id: None,
+    };
+    let question = server.add_comment(&scott_smith_logged_in.token, comment_req).await?;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "exiles",
+        body: "It is".to_string(),
+        parent_id: Some(question.comment.id),
+    };
+    let answer = server.add_comment(&james_joyce_logged_in.token, comment_req).await?;
+    // question is tombstoned, it has a reply
+    server.delete_comment(&scott_smith_logged_in.token, requests::article::DeleteCommentRequest {
+        id: question.comment.id,
+        article_slug: "exiles",
+    }).await?;
+    assert!(0 < purging_server.purge_deleted().await?);
+    let comments = server.get_comments(None, "exiles", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(comments.comments.len(), 3);
+    assert!(comments.comments.iter().any(|comment| comment.comment.id == question.comment.id && comment.comment.deleted));
+
+    // tombstone goes along with its last reply
+    server.delete_comment(&james_joyce_logged_in.token, requests::article::DeleteCommentRequest {
+        id: answer.comment.id,
+        article_slug: "exiles",
+    }).await?;
+    assert_eq!(purging_server.purge_deleted().await?, 2);
+    let comments = server.get_comments(None, "exiles", 
+        filters::CommentThreadFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(comments.comments.len(), 1);
+    server.restore_comment(&james_joyce_logged_in.token, "exiles", answer.comment.id)
+        .await
+        .expect_err("Restored purged comment.");
+    println!(" done.").await;
+
+    // bookmarks
+    print!("{} bookmarks articles...", james_joyce_logged_in.username).await;
+    let article = server.bookmark_article(&james_joyce_logged_in.token, "ulysses-2").await?;
+    assert!(article.bookmarked);
+    assert_eq!(article.article.favorites_count, 0);
+    server.bookmark_article(&james_joyce_logged_in.token, "the-quiet-american").await?;
+    // bookmarking twice keeps single bookmark
+    server.bookmark_article(&james_joyce_logged_in.token, "the-quiet-american").await?;
+    let article = server.get_article_by_slug(Some(&evelyn_waugh_logged_in.token), "ulysses-2").await?;
+    assert!(!article.bookmarked);
+    println!(" done.").await;
+
+    print!("{} gets reading list...", james_joyce_logged_in.username).await;
+    let bookmarks = server.bookmarks(&james_joyce_logged_in.token, filters::LimitOffsetFilter::default()).await?;
+    let slugs = bookmarks.articles.iter()
+        .map(|article| article.article.slug.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(slugs, vec!["the-quiet-american", "ulysses-2"]);
+    assert!(bookmarks.articles.iter().all(|article| article.bookmarked));
+    let bookmarks = server.bookmarks(&james_joyce_logged_in.token, 
+        filters::LimitOffsetFilter::default().limit(1)).await?;
+    assert_eq!(bookmarks.articles.len(), 1);
+    let bookmarks = server.bookmarks(&evelyn_waugh_logged_in.token, filters::LimitOffsetFilter::default()).await?;
+    assert!(bookmarks.articles.is_empty());
+    println!(" done.").await;
+
+    print!("{} removes bookmark...", james_joyce_logged_in.username).await;
+    let article = server.unbookmark_article(&james_joyce_logged_in.token, "the-quiet-american").await?;
+    assert!(!article.bookmarked);
+    let bookmarks = server.bookmarks(&james_joyce_logged_in.token, filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(bookmarks.articles.len(), 1);
+    println!(" done.").await;
+
+    // following tags
+    print!("{} follows tags...", evelyn_waugh_logged_in.username).await;
+    server.follow_tag(&evelyn_waugh_logged_in.token, " ")
+        .await
+        .expect_err("Followed empty tag.");
+    server.follow_tag(&evelyn_waugh_logged_in.token, "Spy").await?;
+    let tags = server.follow_tag(&evelyn_waugh_logged_in.token, "spy").await?;
+    assert_eq!(tags.tags, vec!["spy"]);
+    server.follow_tag(&evelyn_waugh_logged_in.token, "Dublin").await?;
+    let tags = server.followed_tags(&evelyn_waugh_logged_in.token).await?;
+    assert_eq!(tags.tags, vec!["dublin", "spy"]);
+    println!(" done.").await;
+
+    print!("feed articles by followed tags for {}...", evelyn_waugh_logged_in.username).await;
+    let feed = server.feed_articles(&evelyn_waugh_logged_in.token, 
+        filters::FeedFilter::default().mode(filters::FeedMode::Tags), 
+        filters::LimitOffsetFilter::default()).await?;
+    let mut slugs = feed.articles.iter()
+        .map(|article| article.article.slug.as_str())
+        .collect::<Vec<&str>>();
+    slugs.sort();
+    assert_eq!(slugs, vec!["finnegans-wake", "the-quiet-american", "ulysses"]);
+    // wildcard characters in followed tags match only themselves
+    server.follow_tag(&evelyn_waugh_logged_in.token, "%").await?;
+    server.follow_tag(&evelyn_waugh_logged_in.token, "_py").await?;
+    let feed = server.feed_articles(&evelyn_waugh_logged_in.token, 
+        filters::FeedFilter::default().mode(filters::FeedMode::Tags), 
+        filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(feed.articles.len(), 3);
+    server.unfollow_tag(&evelyn_waugh_logged_in.token, "%").await?;
+    server.unfollow_tag(&evelyn_waugh_logged_in.token, "_py").await?;
+    let feed = server.feed_articles(&evelyn_waugh_logged_in.token, 
+        filters::FeedFilter::default().mode(filters::FeedMode::Authors), 
+        filters::LimitOffsetFilter::default()).await?;
+    assert!(feed.articles.is_empty());
+    println!(" done.").await;
+
+    print!("feed articles by followed authors and tags for {}...", evelyn_waugh_logged_in.username).await;
+    server.follow(&evelyn_waugh_logged_in.token, "graham_greene").await?;
+    let feed = server.feed_articles(&evelyn_waugh_logged_in.token, 
+        filters::FeedFilter::default(), 
+        filters::LimitOffsetFilter::default()).await?;
+    let mut slugs = feed.articles.iter()
+        .map(|article| article.article.slug.as_str())
+        .collect::<Vec<&str>>();
+    slugs.sort();
+    // the-quiet-american is both tagged and by followed author, fed once
+    assert_eq!(slugs, vec!["finnegans-wake", "the-quiet-american", "ulysses", "ulysses-2"]);
+    server.unfollow_tag(&evelyn_waugh_logged_in.token, "DUBLIN").await?;
+    let tags = server.followed_tags(&evelyn_waugh_logged_in.token).await?;
+    assert_eq!(tags.tags, vec!["spy"]);
+    println!(" done.").await;
+
+    // tag administration
+    print!("{} describes tag...", scott_smith_logged_in.username).await;
+    match server.update_tag(&scott_smith_logged_in.token, "spy", 
+        requests::tag::UpdateTagRequest { description: Some("Espionage".to_string()) }).await {
+        Err(errors::BackendError::Forbidden) => {},
+        _ => panic!("Non-moderator described tag."),
+    }
+    println!(" failed for forbidden.").await;
+
+    print!("{} describes tag and adds alias...", graham_greene_logged_in.username).await;
+    let tag = server.update_tag(&graham_greene_logged_in.token, "Spy", 
+        requests::tag::UpdateTagRequest { description: Some("Espionage".to_string()) }).await?;
+    assert_eq!(tag.name, "spy");
+    assert_eq!(tag.description.as_deref(), Some("Espionage"));
+    assert_eq!(tag.articles_count, 1);
+    let tag = server.add_tag_alias(&graham_greene_logged_in.token, "spy", 
+        requests::tag::TagAliasRequest { name: "Espionage".to_string() }).await?;
+    assert_eq!(tag.aliases, vec!["espionage"]);
+    server.add_tag_alias(&graham_greene_logged_in.token, "spy", 
+        requests::tag::TagAliasRequest { name: "Dublin".to_string() })
+        .await
+        .expect_err("Tag in use made alias.");
+    let tag = server.get_tag("ESPIONAGE").await?;
+    assert_eq!(tag.name, "spy");
+    server.get_tag("cricket").await.expect_err("Got unknown tag.");
+    println!(" done.").await;
+
+    print!("aliases are resolved on write and query...").await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "our-man-in-havana".to_string(),
+        title: "Our Man in Havana".to_string(), 
+        description: None,
+        body: "Vacuum cleaners".to_string(), 
+        tag_list: Some(vec!["Espionage".to_string(), "spy".to_string(), "Cuba".to_string()]),
+    };
+    let article = server.create_article(&graham_greene_logged_in.token, create_article).await?;
+    assert_eq!(article.article.tag_list.as_deref(), Some("spy,cuba,"));
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default().tag("espionage".to_string()),
+        filters::OrderByFilter::Descending("createdAt"),
+        filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(articles.articles.len(), 2);
+    println!(" done.").await;
+
+    print!("{} merges tags...", graham_greene_logged_in.username).await;
+    let tag = server.merge_tags(&graham_greene_logged_in.token, "stream", 
+        requests::tag::MergeTagRequest { into: "Dublin".to_string() }).await?;
+    assert_eq!(tag.name, "dublin");
+    assert_eq!(tag.aliases, vec!["stream"]);
+    assert_eq!(tag.articles_count, 2);
+    let article = server.get_article_by_slug(None, "finnegans-wake").await?;
+    assert_eq!(article.article.tag_list.as_deref(), Some("dublin,"));
+    assert!(!server.get_tags().await?.tags.contains(&"stream".to_string()));
+    server.merge_tags(&graham_greene_logged_in.token, "stream", 
+        requests::tag::MergeTagRequest { into: "dublin".to_string() })
+        .await
+        .expect_err("Merged tag into itself.");
+    let tag = server.remove_tag_alias(&graham_greene_logged_in.token, "dublin", "stream").await?;
+    assert!(tag.aliases.is_empty());
+    println!(" done.").await;
+
+    print!("article is tagged by canonical tag when alias is added meanwhile...").await;
+    let token = graham_greene_logged_in.token.clone();
+    let create = async_std::task::spawn(async move {
+        let create_article = requests::article::CreateArticleRequest { 
+            slug: "the-third-man".to_string(),
+            title: "The Third Man".to_string(), 
+            description: None,
+            body: "Vienna".to_string(), 
+            tag_list: Some(vec!["Trieste".to_string()]),
+        };
+        server.create_article(&token, create_article).await
+    });
+    let alias = server.add_tag_alias(&graham_greene_logged_in.token, "dublin", 
+        requests::tag::TagAliasRequest { name: "Trieste".to_string() }).await;
+    let article = create.await?;
+    match alias {
+        Ok(_) => assert_eq!(article.article.tag_list.as_deref(), Some("dublin,")),
+        Err(errors::BackendError::ValidationError(_)) => assert_eq!(article.article.tag_list.as_deref(), Some("trieste,")),
+        Err(err) => return Err(err),
+    }
+    server.delete_article(&graham_greene_logged_in.token, "the-third-man").await?;
+    if alias.is_ok() {
+        server.remove_tag_alias(&graham_greene_logged_in.token, "dublin", "trieste").await?;
+    }
+    println!(" done.").await;
+
+    // tag cloud and suggestions
+    print!("getting tag cloud...").await;
+    let tag_cloud = server.tag_cloud(filters::TagCloudFilter::default(), 
+        filters::LimitOffsetFilter::default()).await?;
+    let tags = tag_cloud.tags.iter()
+        .map(|tag| (tag.name.as_str(), tag.articles_count))
+        .collect::<Vec<(&str, i64)>>();
+    assert_eq!(tags, vec![("dublin", 2), ("spy", 2), ("cuba", 1), ("homer", 1), ("play", 1), ("vietnam", 1)]);
+    let tag_cloud = server.tag_cloud(filters::TagCloudFilter::default().sort(filters::TagOrder::Name).days(1), 
+        filters::LimitOffsetFilter::default().limit(2)).await?;
+    let tags = tag_cloud.tags.iter()
+        .map(|tag| tag.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(tags, vec!["cuba", "dublin"]);
+    println!(" done.").await;
+
+    print!("getting tag suggestions...").await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "stamboul-train".to_string(),
+        title: "Stamboul Train".to_string(), 
+        description: None,
+        body: "Orient Express".to_string(), 
+        tag_list: Some(vec!["Spy".to_string(), "Sleeper_Train".to_string()]),
+    };
+    server.create_article(&graham_greene_logged_in.token, create_article).await?;
+    let suggestions = server.autocomplete_tags("S", filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(suggestions.tags, vec!["spy", "sleeper_train"]);
+    let suggestions = server.autocomplete_tags("sleeper_", filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(suggestions.tags, vec!["sleeper_train"]);
+    let suggestions = server.autocomplete_tags("s%", filters::LimitOffsetFilter::default()).await?;
+    assert!(suggestions.tags.is_empty());
+    let suggestions = server.autocomplete_tags("s", filters::LimitOffsetFilter::default().limit(1)).await?;
+    assert_eq!(suggestions.tags, vec!["spy"]);
+    println!(" done.").await;
+
+    // article rankings
+    print!("getting popular articles...").await;
+    server.favorite_article(&james_joyce_logged_in.token, "stamboul-train").await?;
+    server.favorite_article(&evelyn_waugh_logged_in.token, "stamboul-train").await?;
+    server.favorite_article(&graham_greene_logged_in.token, "stamboul-train").await?;
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default(),
+        filters::ArticleSortFilter::default().sort(filters::ArticleSort::Popular).order_by(),
+        filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(articles.articles[0].article.slug, "stamboul-train");
+    assert!(articles.articles.windows(2)
+        .all(|pair| pair[0].article.favorites_count >= pair[1].article.favorites_count));
+    println!(" done.").await;
+
+    print!("getting trending articles...").await;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "scoop",
+        body: "Still a good read".to_string(),
+        parent_id: None,
+    };
+    server.add_comment(&james_joyce_logged_in.token, comment_req).await?;
+    server.refresh_trending().await?;
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default(),
+        filters::ArticleSortFilter::default().sort(filters::ArticleSort::Trending).order_by(),
+        filters::LimitOffsetFilter::default()).await?;
+    let slugs = articles.articles.iter()
+        .map(|article| article.article.slug.as_str())
+        .collect::<Vec<&str>>();
+    let position = |slug| slugs.iter().position(|s| *s == slug).unwrap();
+    // favorites outweigh a single comment, articles without recent activity come last
+    assert!(position("stamboul-train") < position("scoop"));
+    assert!(position("scoop") < position("dubliners"));
+    assert!(position("scoop") < position("our-man-in-havana"));
+    println!(" done.").await;
+
+    // related articles
+    print!("getting articles related to {}...", "ulysses").await;
+    let related = server.related_articles(None, "ulysses", filters::LimitOffsetFilter::default()).await?;
+    let slugs = related.articles.iter()
+        .map(|article| article.article.slug.as_str())
+        .collect::<Vec<&str>>();
+    // shares tag and author
+    assert_eq!(slugs[0], "finnegans-wake");
+    assert!(!slugs.contains(&"ulysses"));
+    // shares nothing
+    assert!(!slugs.contains(&"scoop"));
+    let related = server.related_articles(None, "ulysses", filters::LimitOffsetFilter::default().limit(1)).await?;
+    assert_eq!(related.articles.len(), 1);
+    println!(" done.").await;
+
+    print!("getting related articles by co-favorites...").await;
+    // james, evelyn and graham favorited stamboul-train, graham favorites scoop as well
+    server.favorite_article(&graham_greene_logged_in.token, "scoop").await?;
+    let related = server.related_articles(None, "scoop", filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(related.articles[0].article.slug, "stamboul-train");
+    println!(" done.").await;
+
+    print!("getting related articles without muted and blocked authors...").await;
+    // scott muted james
+    let related = server.related_articles(Some(&scott_smith_logged_in.token), "ulysses", 
+        filters::LimitOffsetFilter::default()).await?;
+    assert!(related.articles.iter().all(|article| article.article.author != "james_joyce"));
+    server.block(&evelyn_waugh_logged_in.token, "graham_greene").await?;
+    let related = server.related_articles(Some(&evelyn_waugh_logged_in.token), "scoop", 
+        filters::LimitOffsetFilter::default()).await?;
+    assert!(related.articles.iter().all(|article| article.article.author != "graham_greene"));
+    server.unblock(&evelyn_waugh_logged_in.token, "graham_greene").await?;
+    println!(" done.").await;
+
+    // article views
+    print!("counting views of {}...", "stamboul-train").await;
+    let anonymous = requests::article::ViewContext {
+        remote: Some("10.0.0.1".to_string()),
+        user_agent: Some("Mozilla/5.0".to_string()),
+    };
+    let article = server.view_article(None, "stamboul-train", &anonymous).await?;
+    assert_eq!(article.article.views_count, 1);
+    // repeated within deduplication window
+    let article = server.view_article(None, "stamboul-train", &anonymous).await?;
+    assert_eq!(article.article.views_count, 1);
+    let crawler = requests::article::ViewContext {
+        remote: Some("10.0.0.2".to_string()),
+        user_agent: Some("Googlebot/2.1".to_string()),
+    };
+    let article = server.view_article(None, "stamboul-train", &crawler).await?;
+    assert_eq!(article.article.views_count, 1);
+    // own view
+    let article = server.view_article(Some(&graham_greene_logged_in.token), "stamboul-train", 
+        &requests::article::ViewContext::default()).await?;
+    assert_eq!(article.article.views_count, 1);
+    let article = server.view_article(Some(&james_joyce_logged_in.token), "stamboul-train", 
+        &requests::article::ViewContext::default()).await?;
+    assert_eq!(article.article.views_count, 2);
+    println!(" done.").await;
+
+    print!("getting author statistics of {}...", graham_greene_logged_in.username).await;
+    let stats = server.author_stats(&graham_greene_logged_in.token, filters::StatsFilter::default()).await?;
+    let stamboul_train = stats.articles.iter()
+        .find(|article| article.slug == "stamboul-train")
+        .unwrap();
+    assert_eq!(stamboul_train.views_count, 2);
+    assert_eq!(stamboul_train.favorites_count, 3);
+    assert_eq!(stamboul_train.buckets.len(), 1);
+    assert_eq!(stamboul_train.buckets[0].views, 2);
+    assert_eq!(stamboul_train.buckets[0].favorites, 3);
+    let stats = server.author_stats(&graham_greene_logged_in.token, 
+        filters::StatsFilter::default().bucket(filters::StatsBucketSize::Month)).await?;
+    assert!(stats.articles.iter().all(|article| article.buckets.len() <= 1));
+    assert!(stats.articles.iter().all(|article| article.slug != "scoop"));
+    println!(" done.").await;
+
+    print!("pruning single views keeps view totals...").await;
+    let mut cfg = Config::from_env();
+    cfg.drop_database = false;
+    cfg.view_dedup_minutes = 0;
+    cfg.trending_window_hours = 0;
+    let mut pruning_server = Server::with_config(cfg);
+    pruning_server.connect().await?;
+    assert!(0 < pruning_server.prune_views().await?);
+    let article = server.get_article_by_slug(None, "stamboul-train").await?;
+    assert_eq!(article.article.views_count, 2);
+    let stats = server.author_stats(&graham_greene_logged_in.token, filters::StatsFilter::default()).await?;
+    let stamboul_train = stats.articles.iter()
+        .find(|article| article.slug == "stamboul-train")
+        .unwrap();
+    assert_eq!(stamboul_train.views_count, 2);
+    assert_eq!(stamboul_train.buckets[0].views, 2);
+    println!(" done.").await;
+
+    // rendered bodies
+    print!("rendering article body as html...").await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "brighton-rock".to_string(),
+        title: "Brighton Rock".to_string(), 
+        description: None,
+        body: "# Pinkie\n\n*Hale* knew <script>alert(1)</script> [they](javascript:alert(1)) meant to murder him".to_string(), 
+        tag_list: None,
+    };
+    let article = server.create_article(&graham_greene_logged_in.token, create_article).await?;
+    assert!(article.body_html.is_none());
+    let article = server.render_article(article, filters::BodyFormat::Html).await?;
+    let body_html = article.body_html.unwrap();
+    assert!(body_html.contains("<h1>Pinkie</h1>"));
+    assert!(body_html.contains("<em>Hale</em>"));
+    assert!(!body_html.contains("script"));
+    assert!(!body_html.contains("javascript"));
+    let article = server.get_article_by_slug(None, "brighton-rock").await?;
+    let article = server.render_article(article, filters::BodyFormat::Markdown).await?;
+    assert!(article.body_html.is_none());
+    println!(" done.").await;
+
+    print!("rendering updated article body as html...").await;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("**Ida** knew"),
+        slug: "brighton-rock",
+        if_match: None,
+    };
+    server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    let articles = server.get_articles(None, 
+        filters::ArticleFilterByValues::default().author("graham_greene".to_string()),
+        filters::OrderByFilter::default(),
+        filters::LimitOffsetFilter::default()).await?;
+    let articles = server.render_articles(articles, filters::BodyFormat::Html).await?;
+    let brighton_rock = articles.articles.iter()
+        .find(|article| article.article.slug == "brighton-rock")
+        .unwrap();
+    assert_eq!(brighton_rock.body_html.as_deref(), Some("<p><strong>Ida</strong> knew</p>\n"));
+    assert!(articles.articles.iter().all(|article| article.body_html.is_some()));
+    println!(" done.").await;
+
+    print!("rendering comments as html...").await;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "brighton-rock",
+        body: "<img src=x onerror=alert(1)> `Pinkie`".to_string(),
+        parent_id: None,
+    };
+    server.add_comment(&james_joyce_logged_in.token, comment_req).await?;
+    let comments = server.get_comments(None, "brighton-rock", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    let comments = server.render_comments(comments, filters::BodyFormat::Html);
+    let body_html = comments.comments[0].body_html.as_deref().unwrap();
+    assert!(body_html.contains("<code>Pinkie</code>"));
+    assert!(!body_html.contains("onerror"));
+    println!(" done.").await;
+
+    // reading time and excerpts
+    print!("computing word count and excerpt of {}...", "the-end-of-the-affair").await;
+    let paragraph = "Bendrix tells the story of his affair with Sarah during the Blitz.";
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "the-end-of-the-affair".to_string(),
+        title: "The End of the Affair".to_string(), 
+        description: None,
+        body: format!("## London\n\n**{}**\n\n{}", paragraph, vec![paragraph; 40].join(" ")), 
+        tag_list: None,
+    };
+    let article = server.create_article(&graham_greene_logged_in.token, create_article).await?;
+    assert_eq!(article.article.word_count, 1 + 12 * 41);
+    assert_eq!(article.article.reading_time_minutes, 3);
+    let description = article.article.description.unwrap();
+    assert!(description.starts_with("London Bendrix tells the story"));
+    assert!(description.ends_with('…'));
+    assert!(!description.contains("**"));
+    println!(" done.").await;
+
+    print!("updating excerpt along with body...").await;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("Sarah keeps her vow"),
+        slug: "the-end-of-the-affair",
+        if_match: None,
+    };
+    let article = server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    assert_eq!(article.article.word_count, 4);
+    assert_eq!(article.article.reading_time_minutes, 1);
+    assert_eq!(article.article.description.as_deref(), Some("Sarah keeps her vow"));
+    // written description is kept on body change
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().description("A love story"),
+        slug: "the-end-of-the-affair",
+        if_match: None,
+    };
+    server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("Bendrix hates God"),
+        slug: "the-end-of-the-affair",
+        if_match: None,
+    };
+    let article = server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    assert_eq!(article.article.word_count, 3);
+    assert_eq!(article.article.description.as_deref(), Some("A love story"));
+    // cleared description is generated again, as on create
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().description(""),
+        slug: "the-end-of-the-affair",
+        if_match: None,
+    };
+    let article = server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    assert_eq!(article.article.description.as_deref(), Some("Bendrix hates God"));
+    // overlong first word is cut
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body(&format!("https://example.com/{}", "ä".repeat(300))),
+        slug: "the-end-of-the-affair",
+        if_match: None,
+    };
+    let article = server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    let description = article.article.description.unwrap();
+    assert!(description.starts_with("https://example.com/ää"));
+    assert!(description.ends_with('…'));
+    assert!(description.len() <= 200 + '…'.len_utf8());
+    println!(" done.").await;
+
+    // uploads
+    print!("{} uploads image...", scott_smith_logged_in.username).await;
+    let png = [b"\x89PNG\r\n\x1a\n".as_slice(), &[0; 92]].concat();
+    let upload = server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: png.clone() }).await?;
+    assert_eq!(upload.content_type, "image/png");
+    assert_eq!(upload.size, 100);
+    assert_eq!(upload.id.len(), 64);
+    // content addressed
+    let same_upload = server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: png.clone() }).await?;
+    assert_eq!(same_upload.id, upload.id);
+    let (served, data) = server.get_upload(&upload.id).await?;
+    assert_eq!(served.content_type, "image/png");
+    assert_eq!(data, png);
+    println!(" done.").await;
+
+    print!("uploading unsupported file...").await;
+    match server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: b"<svg onload=alert(1)>".to_vec() }).await {
+        Err(errors::BackendError::ValidationError(_)) => (),
+        res => panic!("Uploaded unsupported file: {:?}", res),
+    }
+    println!(" failed as expected.").await;
+
+    print!("uploading beyond size limit and quota...").await;
+    let gif = |fill: u8, len: usize| [b"GIF89a".as_slice(), &vec![fill; len - 6]].concat();
+    match server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: gif(0, 2000) }).await {
+        Err(errors::BackendError::UploadTooLarge(_)) => (),
+        res => panic!("Uploaded too large file: {:?}", res),
+    }
+    let jpeg = [b"\xff\xd8\xff".as_slice(), &[0; 997]].concat();
+    let jpeg_upload = server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: jpeg }).await?;
+    match server.upload(&scott_smith_logged_in.token, 
+        requests::upload::UploadRequest { data: gif(1, 1000) }).await {
+        Err(errors::BackendError::UploadTooLarge(_)) => (),
+        res => panic!("Uploaded beyond quota: {:?}", res),
+    }
+    // quota is per user
+    server.upload(&james_joyce_logged_in.token, 
+        requests::upload::UploadRequest { data: gif(1, 1000) }).await?;
+    println!(" failed as expected.").await;
+
+    print!("purging orphan uploads...").await;
+    let set_profile = requests::user::UserUpdateRequest {
+        image: Some(upload.url()),
+        ..Default::default()
+    };
+    server.update_user(&scott_smith_logged_in.token, set_profile).await?;
+    assert_eq!(server.purge_orphan_uploads().await?, 2);
+    server.get_upload(&upload.id).await?;
+    match server.get_upload(&jpeg_upload.id).await {
+        Err(errors::BackendError::NoUploadFound(_)) => (),
+        res => panic!("Orphan upload not purged: {:?}", res),
+    }
+    println!(" done.").await;
+
+    print!("uploading concurrently within quota...").await;
+    let uploads = (0..3u8)
+        .map(|fill| {
+            let token = james_joyce_logged_in.token.clone();
+            let data = gif(fill, 900);
+            async_std::task::spawn(async move {
+                server.upload(&token, requests::upload::UploadRequest { data }).await
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut stored = 0;
+    for upload in uploads {
+        match upload.await {
+            Ok(_) => stored += 1,
+            Err(errors::BackendError::UploadTooLarge(_)) => (),
+            Err(err) => return Err(err),
+        }
+    }
+    assert_eq!(stored, 2);
+    assert_eq!(server.purge_orphan_uploads().await?, 2);
+    println!(" done.").await;
+
+    // avatars
+    print!("getting identicon of {}...", graham_greene_logged_in.username).await;
+    let profile = server.profile(None, "graham_greene").await?;
+    assert_eq!(profile.image.as_deref(), Some("/api/profiles/graham_greene/avatar"));
+    let identicon = match server.avatar("graham_greene", filters::AvatarFilter::default()).await? {
+        Avatar::Generated(png) => png,
+        avatar => panic!("No identicon generated: {:?}", avatar),
+    };
+    let decoded = image::load_from_memory(&identicon).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (256, 256));
+    // deterministic
+    match server.avatar("graham_greene", filters::AvatarFilter::default()).await? {
+        Avatar::Generated(png) => assert_eq!(png, identicon),
+        avatar => panic!("No identicon generated: {:?}", avatar),
+    }
+    match server.avatar("graham_greene", filters::AvatarFilter::default().size(64)).await? {
+        Avatar::Generated(png) => assert_eq!(image::load_from_memory(&png).unwrap().width(), 64),
+        avatar => panic!("No identicon generated: {:?}", avatar),
+    }
+    println!(" done.").await;
+
+    print!("{} sets avatar...", graham_greene_logged_in.username).await;
+    let mut jpeg = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(60, 40, image::Rgb([200, 30, 30])))
+        .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(80))
+        .unwrap();
+    let user = server.set_avatar(&graham_greene_logged_in.token, 
+        requests::upload::UploadRequest { data: jpeg.into_inner() }).await?;
+    let image_url = user.image.unwrap();
+    assert!(image_url.starts_with("/uploads/"));
+    let upload_id = match server.avatar("graham_greene", filters::AvatarFilter::default().size(100)).await? {
+        Avatar::Url(url) => url.trim_start_matches("/uploads/").to_string(),
+        avatar => panic!("No avatar stored: {:?}", avatar),
+    };
+    // cropped to square of the nearest larger size
+    let (upload, data) = server.get_upload(&upload_id).await?;
+    assert_eq!(upload.content_type, "image/png");
+    let decoded = image::load_from_memory(&data).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (128, 128));
+    match server.set_avatar(&graham_greene_logged_in.token, 
+        requests::upload::UploadRequest { data: b"GIF89a".to_vec() }).await {
+        Err(errors::BackendError::ValidationError(_)) => (),
+        res => panic!("Set unsupported avatar: {:?}", res),
+    }
+    // dimensions are checked before decoding pixels
+    let mut wide_png = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image::GrayImage::new(5000, 1))
+        .write_to(&mut wide_png, image::ImageOutputFormat::Png)
+        .unwrap();
+    match server.set_avatar(&graham_greene_logged_in.token, 
+        requests::upload::UploadRequest { data: wide_png.into_inner() }).await {
+        Err(errors::BackendError::ValidationError(_)) => (),
+        res => panic!("Set avatar of huge dimensions: {:?}", res),
+    }
+    println!(" done.").await;
+
+    print!("{} sets avatar taken by rotated camera...", james_joyce_logged_in.username).await;
+    // red top and blue bottom half, to be turned 90 degrees clockwise
+    let mut jpeg = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(60, 20, |_, y| 
+        if y < 10 { image::Rgb([220, 0, 0]) } else { image::Rgb([0, 0, 220]) }))
+        .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(80))
+        .unwrap();
+    let mut jpeg = jpeg.into_inner();
+    // APP1 segment right after SOI marker with big endian TIFF holding Orientation=6
+    let exif: [u8; 36] = [
+        0xFF, 0xE1, 0x00, 0x22, b'E', b'x', b'i', b'f', 0, 0,
+        b'M', b'M', 0x00, 0x2A, 0x00, 0x00, 0x00, 0x08,
+        0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+    jpeg.splice(2..2, exif);
+    server.set_avatar(&james_joyce_logged_in.token, 
+        requests::upload::UploadRequest { data: jpeg }).await?;
+    let upload_id = match server.avatar("james_joyce", filters::AvatarFilter::default().size(64)).await? {
+        Avatar::Url(url) => url.trim_start_matches("/uploads/").to_string(),
+        avatar => panic!("No avatar stored: {:?}", avatar),
+    };
+    let (_, data) = server.get_upload(&upload_id).await?;
+    let decoded = image::load_from_memory(&data).unwrap().to_rgb8();
+    // bottom turned to the left
+    assert!(decoded.get_pixel(5, 10)[2] > 150);
+    assert!(decoded.get_pixel(58, 54)[0] > 150);
+    // smaller sizes are not orphans
+    server.purge_orphan_uploads().await?;
+    server.get_upload(&upload_id).await?;
+    println!(" done.").await;
+
+    print!("getting avatar of profile with external image...").await;
+    let set_profile = requests::user::UserUpdateRequest {
+        image: Some("https://example.com/greene.jpg".to_string()),
+        ..Default::default()
+    };
+    server.update_user(&graham_greene_logged_in.token, set_profile).await?;
+    match server.avatar("graham_greene", filters::AvatarFilter::default().size(64)).await? {
+        Avatar::Url(url) => assert_eq!(url, "https://example.com/greene.jpg"),
+        avatar => panic!("Profile image not used: {:?}", avatar),
+    }
+    println!(" done.").await;
+
+    // mentions
+    print!("{} mentions users in article...", graham_greene_logged_in.username).await;
+    let create_article = requests::article::CreateArticleRequest { 
+        slug: "the-power-and-the-glory".to_string(),
+        title: "The Power and the Glory".to_string(), 
+        description: None,
+        body: "For @james_joyce and @nobody, not for `@scott_smith` nor priest@evelyn_waugh".to_string(), 
+        tag_list: None,
+    };
+    let article = server.create_article(&graham_greene_logged_in.token, create_article).await?;
+    assert_eq!(article.mentions, vec!["james_joyce"]);
+    let notifications = server.get_notifications(&james_joyce_logged_in.token, 
+        filters::NotificationFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    let mentions = notifications.notifications.iter()
+        .filter(|notification| notification.notification.kind == notification::NotificationKind::Mention)
+        .collect::<Vec<_>>();
+    assert_eq!(mentions.len(), 1);
+    assert_eq!(mentions[0].notification.article_slug.as_deref(), Some("the-power-and-the-glory"));
+    println!(" done.").await;
+
+    print!("{} mentions another user on update...", graham_greene_logged_in.username).await;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("For @james_joyce and @scott_smith"),
+        slug: "the-power-and-the-glory",
+        if_match: None,
+    };
+    let article = server.update_article(&graham_greene_logged_in.token, update_article_req).await?;
+    assert_eq!(article.mentions, vec!["james_joyce", "scott_smith"]);
+    // already mentioned user is not notified again
+    let notifications = server.get_notifications(&james_joyce_logged_in.token, 
+        filters::NotificationFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert_eq!(notifications.notifications.iter()
+        .filter(|notification| notification.notification.kind == notification::NotificationKind::Mention)
+        .count(), 1);
+    let notifications = server.get_notifications(&scott_smith_logged_in.token, 
+        filters::NotificationFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(notifications.notifications.iter()
+        .any(|notification| notification.notification.kind == notification::NotificationKind::Mention));
+    let article = server.get_article_by_slug(None, "the-power-and-the-glory").await?;
+    assert_eq!(article.mentions, vec!["james_joyce", "scott_smith"]);
+    println!(" done.").await;
+
+    print!("mentioning user who blocked the author in comment...").await;
+    server.block(&james_joyce_logged_in.token, "scott_smith").await?;
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-power-and-the-glory",
+        body: "Ask @james_joyce".to_string(),
+        parent_id: None,
+    };
+    let comment = server.add_comment(&scott_smith_logged_in.token, comment_req).await?;
+    assert_eq!(comment.mentions, vec!["james_joyce"]);
+    let notifications = server.get_notifications(&james_joyce_logged_in.token, 
+        filters::NotificationFilter::default(), filters::LimitOffsetFilter::default()).await?;
+    assert!(notifications.notifications.iter()
+        .all(|notification| notification.notification.comment_id != Some(comment.comment.id)));
+    let comments = server.get_comments(None, "the-power-and-the-glory", 
+        filters::CommentThreadFilter::default(), 
+        filters::LimitOffsetFilter::default()
+    ).await?;
+    assert_eq!(comments.comments[0].mentions, vec!["james_joyce"]);
+    server.unblock(&james_joyce_logged_in.token, "scott_smith").await?;
+    println!(" done, not notified.").await;
+
+    print!("mentioning too many users...").await;
+    let body = (0..20).map(|n| format!("@nobody_{} ", n)).collect::<String>() + "@james_joyce";
+    let comment_req = requests::article::AddCommentRequest {
+        article_slug: "the-power-and-the-glory",
+        body,
+        parent_id: None,
+    };
+    let comment = server.add_comment(&scott_smith_logged_in.token, comment_req).await?;
+    assert!(comment.mentions.is_empty());
+    println!(" done, only first ones looked for.").await;
+
+    print!("mentioning users in hidden article...").await;
+    let james_unread = server.unread_notifications_count(&james_joyce_logged_in.token).await?.unread_count;
+    let graham_unread = server.unread_notifications_count(&graham_greene_logged_in.token).await?.unread_count;
+    let update_article_req = requests::article::UpdateArticleRequest {
+        article: article::UpdateArticle::default().body("For @james_joyce and @graham_greene"),
+        slug: "notes",
+        if_match: None,
+    };
+    let article = server.update_article(&scott_smith_logged_in.token, update_article_req).await?;
+    assert_eq!(article.mentions, vec!["james_joyce", "graham_greene"]);
+    assert_eq!(server.unread_notifications_count(&james_joyce_logged_in.token).await?.unread_count, james_unread);
+    // moderators still see it
+    assert_eq!(server.unread_notifications_count(&graham_greene_logged_in.token).await?.unread_count, 
+        graham_unread + 1);
+    println!(" done, only moderator notified.").await;
+
+    print!("creating articles with the same slug concurrently...").await;
+    let creates = (0..4).map(|_| {
+        let token = james_joyce_logged_in.token.clone();
+        async_std::task::spawn(async move {
+            let create_article = requests::article::CreateArticleRequest { 
+                slug: "a-portrait-of-the-artist".to_string(),
+                title: "A Portrait of the Artist".to_string(), 
+                description: None,
+                body: "Some body".to_string(), 
+                tag_list: None,
+            };
+            server.create_article(&token, create_article).await
+        })
+    }).collect::<Vec<_>>();
+    let mut slugs = Vec::new();
+    for create in creates {
+        slugs.push(create.await?.article.slug);
+    }
+    slugs.sort();
+    assert_eq!(slugs, vec!["a-portrait-of-the-artist", "a-portrait-of-the-artist-2", 
+        "a-portrait-of-the-artist-3", "a-portrait-of-the-artist-4"]);
+    println!(" done.").await;
+
     Ok(())
 }
\ No newline at end of file